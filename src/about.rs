@@ -0,0 +1,120 @@
+use crate::keymap::{Action, Keymap};
+use crate::models::{Download, DownloadStatus, HistoryEntry};
+use crate::network::escape_html;
+use std::collections::BTreeMap;
+
+/// Built-in pages reachable by typing their address
+pub const ABOUT_PAGES: &[&str] = &[
+    "about:blank",
+    "about:help",
+    "about:history",
+    "about:bookmarks",
+    "about:downloads",
+];
+
+/// Whether `url` names a built-in page, which is rendered locally instead of fetched
+pub fn is_about_url(url: &str) -> bool {
+    url.trim()
+        .get(..6)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("about:"))
+}
+
+/// Title and HTML of the built-in page at `url`; unknown names get a page listing the real ones
+pub fn page(
+    url: &str,
+    history: &[HistoryEntry],
+    downloads: &BTreeMap<usize, Download>,
+    keymap: &Keymap,
+) -> (String, String) {
+    let name = url.trim().to_ascii_lowercase();
+    match name.as_str() {
+        "about:blank" => (String::from("about:blank"), String::new()),
+        "about:help" => (
+            String::from("Help"),
+            include_str!("../assets/help.html").to_string(),
+        ),
+        "about:history" => (String::from("History"), history_html(history)),
+        "about:bookmarks" => (String::from("Bookmarks"), bookmarks_html(keymap)),
+        "about:downloads" => (String::from("Downloads"), downloads_html(downloads)),
+        _ => (String::from("Not Found"), unknown_html(url)),
+    }
+}
+
+/// The tab's back stack, most recent first
+fn history_html(history: &[HistoryEntry]) -> String {
+    if history.is_empty() {
+        return String::from("<h1>History</h1><hr><p>No pages visited in this tab yet.</p>");
+    }
+    let items: String = history
+        .iter()
+        .rev()
+        .map(|entry| {
+            let url = escape_html(&entry.url);
+            format!("<li><a href=\"{url}\">{url}</a></li>")
+        })
+        .collect();
+    format!("<h1>History</h1><hr><ol>{items}</ol>")
+}
+
+/// Points to marks instead, named by the keys they're bound to
+fn bookmarks_html(keymap: &Keymap) -> String {
+    let marks = match (
+        keymap.normal_key(Action::SetMark),
+        keymap.normal_key(Action::JumpToMark),
+    ) {
+        (Some(set), Some(jump)) => format!(
+            "<p>Use marks (<b>{}</b> and <b>{}</b>) to return to places on a page.</p>",
+            escape_html(&set),
+            escape_html(&jump)
+        ),
+        _ => String::from(
+            "<p>Bind <b>set_mark</b> and <b>jump_to_mark</b> in the keymap to return to \
+             places on a page.</p>",
+        ),
+    };
+    format!("<h1>Bookmarks</h1><hr><p>Bookmarks aren't supported yet.</p>{marks}")
+}
+
+/// Every download this session, oldest first, with where finished ones were saved
+fn downloads_html(downloads: &BTreeMap<usize, Download>) -> String {
+    if downloads.is_empty() {
+        return String::from("<h1>Downloads</h1><hr><p>Nothing downloaded yet.</p>");
+    }
+    let items: String = downloads
+        .values()
+        .map(|download| {
+            let status = match &download.status {
+                DownloadStatus::Active => format!(
+                    "downloading, {}",
+                    crate::ui::format_bytes(download.bytes_downloaded)
+                ),
+                DownloadStatus::Completed => match &download.saved_path {
+                    Some(path) => format!("saved to {}", path),
+                    None => String::from("finished"),
+                },
+                DownloadStatus::Failed(reason) => format!("failed: {}", reason),
+                DownloadStatus::Cancelled => String::from("cancelled"),
+            };
+            format!(
+                "<li><b>{}</b> - {}<br><a href=\"{url}\">{url}</a></li>",
+                escape_html(&download.filename),
+                escape_html(&status),
+                url = escape_html(&download.url)
+            )
+        })
+        .collect();
+    format!("<h1>Downloads</h1><hr><ul>{items}</ul>")
+}
+
+fn unknown_html(url: &str) -> String {
+    let pages: String = ABOUT_PAGES
+        .iter()
+        .map(|page| format!("<li><a href=\"{page}\">{page}</a></li>"))
+        .collect();
+    format!(
+        "<h1>Not Found</h1><hr>\
+         <p>There is no built-in page called <b>{}</b>. These exist:</p>\
+         <ul>{pages}</ul>",
+        escape_html(url.trim())
+    )
+}
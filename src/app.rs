@@ -1,20 +1,48 @@
 use crate::constants::{
     DEFAULT_TAB_INDEX, INITIAL_ID_COUNTER, INITIAL_TAB_ID, MARGINALIA_SEARCH_URL,
-    MAX_PAGE_SIZE_BYTES,
+    MIN_COLUMN_WIDTH, STATUS_MESSAGE_TIMEOUT_MS, TAB_BAR_HEIGHT, UI_BORDER_WIDTH, URL_BAR_HEIGHT,
+    USER_AGENT, USER_AGENT_PRESETS,
 };
-use crate::models::{InputMode, LinkRegion, SearchState, Selection};
-use crate::network::{NetworkManager, NetworkResponse, attempt_jump, parse_html_metadata};
-use crate::renderer::DomRenderer;
+use crate::about;
+use crate::config::{Config, DownloadConflictPolicy};
+use crate::gemini::{self, KnownHosts};
+use crate::keymap::{Action, Keymap};
+use crate::launcher::{
+    Launcher, PipeRunner, open_command, shell_command, system_launcher, system_pipe_runner,
+};
+use crate::models::{
+    AuthPrompt, BasicCredentials, ConflictChoice, Download, DownloadStatus, Form, FormField,
+    HistoryEntry, InputMode, InputRegion, LinkFilter, LinkRegion, Mark, NavigationSnapshot,
+    PageInfo, PendingLoad, SearchState, Selection, SplitPane,
+};
+use crate::network::{
+    HostThrottle, NetworkManager, NetworkResponse, attempt_jump, auth_host_key, basic_auth_realm,
+    certificate_problem, decode_body, describe_fetch_error, feed_to_html, is_ambiguous_link,
+    is_downloadable_file, is_i2p_url, is_markdown, is_xml, looks_like_address, markdown_to_html,
+    page_info_from_response, parent_url, parse_html_metadata, probe_link_is_download,
+    read_body_limited, resolve_user_agent, sitemap_to_html, target_violation,
+    unique_download_path, user_agent_preset_name,
+};
+use crate::renderer::{
+    DomRenderer, add_line_numbers, highlight_html_source, parse_delimited, render_html_to_lines,
+    table_lines, wrap_lines,
+};
+use crate::session::{Session, SessionTab};
 
+use ratatui::layout::Rect;
 use ratatui::text::Line;
 use reqwest::StatusCode;
+use ring::digest;
 use scraper::Html;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::path::Path;
 use std::sync::Arc;
-use tokio::sync::mpsc;
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot};
+use tokio_util::sync::CancellationToken;
+use unicode_width::UnicodeWidthChar;
 use url::Url;
 
-use directories::UserDirs;
 use futures_util::StreamExt;
 use tokio::io::AsyncWriteExt; // Required for streaming to file
 
@@ -25,7 +53,7 @@ pub struct BrowserTab {
     pub link_regions: Vec<LinkRegion>,
     pub page_title: String,
     pub scroll: usize,
-    pub history: Vec<String>,
+    pub history: Vec<HistoryEntry>,
     pub selected_link_index: usize,
     pub input_mode: InputMode,
     pub status_message: String,
@@ -37,6 +65,49 @@ pub struct BrowserTab {
     pub download_state: Option<crate::models::Download>,
     pub search_state: Option<SearchState>,
     pub download_prompt: Option<crate::models::DownloadPrompt>,
+    pub drag_anchor: Option<(usize, usize)>,
+    pub url_cursor: usize,
+    /// When a transient status message reverts to "Ready", and the message it applies to
+    pub status_expiry: Option<(Instant, String)>,
+    pub page_info: Option<PageInfo>,
+    pub show_page_info: bool,
+    pub forms: Vec<Form>,
+    pub input_regions: Vec<InputRegion>,
+    /// Index into `input_regions` of the field being typed into
+    pub focused_input: Option<usize>,
+    pub auth_prompt: Option<AuthPrompt>,
+    /// Columns hidden off the left edge of lines wider than the viewport
+    pub h_scroll: usize,
+    /// Reflow long lines to the viewport; when off they clip and scroll horizontally
+    pub wrap_lines: bool,
+    /// Scroll to restore once the page being loaded by a back navigation arrives
+    pub pending_scroll: Option<usize>,
+    /// Positions saved on the current page, dropped when the page changes
+    pub marks: HashMap<char, Mark>,
+    /// Set when the page is CSV/TSV text, which renders as a table instead of HTML
+    pub table_delimiter: Option<char>,
+    /// When the page being fetched was requested, while it's still loading
+    pub loading_since: Option<Instant>,
+    /// Show only the page's main content, without navigation and other boilerplate
+    pub reader_mode: bool,
+    /// Absolute `<base href>` of the loaded page; relative links resolve against it when set
+    pub base_url: Option<String>,
+    /// Absolute URL of the RSS/Atom feed the loaded page advertises
+    pub feed_url: Option<String>,
+    /// URL of the page on screen, which may differ from `url_input` while editing or loading
+    pub loaded_url: String,
+    /// The page request in flight, if any; Esc cancels it
+    pub pending_load: Option<PendingLoad>,
+    /// Sent as the Referer of the next page request, then cleared
+    pub referer: Option<String>,
+    /// Widest this tab's text reflows to, in columns; `None` fills the terminal
+    pub column_width: Option<usize>,
+    /// Columns taken by the source view's line numbers at the start of every rendered line;
+    /// search, copied text and `page_text` skip them
+    pub gutter_width: usize,
+    pub link_filter: Option<LinkFilter>,
+    /// `host:port` of the page whose certificate was just refused, while its warning is showing
+    pub certificate_host: Option<String>,
 }
 
 impl BrowserTab {
@@ -51,23 +122,43 @@ impl BrowserTab {
             end_char: self.cursor_char,
         });
     }
+    /// Resolve a link or form target on the current page, honouring `<base href>`
+    pub fn resolve_link(&self, target: &str) -> String {
+        let base = self.base_url.as_deref().unwrap_or(&self.url_input);
+        crate::network::resolve_url(base, target)
+    }
     pub fn extract_text_from_selection(&self) -> String {
         match &self.selection {
-            Some(sel) => sel.extract_text(&self.rendered_content),
+            Some(sel) => sel.extract_text_past_gutter(&self.rendered_content, self.gutter_width),
             None => String::new(),
         }
     }
+    /// The whole rendered page as plain text, one line per rendered line
+    pub fn page_text(&self) -> String {
+        self.rendered_content
+            .iter()
+            .map(|line| {
+                let mut column = 0;
+                let text = line.spans.iter().flat_map(|span| span.content.chars());
+                text.filter(|ch| {
+                    let past_gutter = column >= self.gutter_width;
+                    column += UnicodeWidthChar::width(*ch).unwrap_or(0);
+                    past_gutter
+                })
+                .collect::<String>()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
     pub fn new(id: usize, initial_url: String) -> Self {
-        let help_html = include_str!("../assets/help.html");
-        let document = Html::parse_document(help_html);
-        let mut renderer = DomRenderer::new(100);
-        renderer.render(&document);
+        let (rendered_content, link_regions) =
+            render_html_to_lines(include_str!("../assets/help.html"), 100);
 
         Self {
             id,
-            url_input: initial_url,
-            rendered_content: renderer.lines,
-            link_regions: renderer.links,
+            url_input: initial_url.clone(),
+            rendered_content,
+            link_regions,
             page_title: String::from("New Tab"),
             scroll: 0,
             history: Vec::new(),
@@ -82,7 +173,295 @@ impl BrowserTab {
             download_state: None,
             search_state: None,
             download_prompt: None,
+            drag_anchor: None,
+            url_cursor: 0,
+            status_expiry: None,
+            page_info: None,
+            show_page_info: false,
+            forms: Vec::new(),
+            input_regions: Vec::new(),
+            focused_input: None,
+            auth_prompt: None,
+            h_scroll: 0,
+            wrap_lines: true,
+            pending_scroll: None,
+            marks: HashMap::new(),
+            table_delimiter: None,
+            loading_since: None,
+            reader_mode: false,
+            base_url: None,
+            feed_url: None,
+            loaded_url: initial_url,
+            pending_load: None,
+            referer: None,
+            column_width: None,
+            gutter_width: 0,
+            link_filter: None,
+            certificate_host: None,
+        }
+    }
+
+    /// Status line shown while `url_input` is being fetched
+    pub fn loading_message(&self) -> String {
+        format!("Loading {}...", crate::network::display_url(&self.url_input))
+    }
+
+    /// Start tracking a page request, cancelling any still in flight. The loading state shows
+    /// straight away, over the page already on screen, rather than once the request is underway.
+    /// That page is remembered so that cancelling puts its address and title back.
+    pub fn begin_load(&mut self) -> CancellationToken {
+        let cancel = CancellationToken::new();
+        let load = match self.pending_load.take() {
+            Some(previous) => {
+                previous.cancel.cancel();
+                PendingLoad {
+                    cancel: cancel.clone(),
+                    ..previous
+                }
+            }
+            None => PendingLoad {
+                cancel: cancel.clone(),
+                previous_url: self.loaded_url.clone(),
+                previous_title: self.page_title.clone(),
+                previous_info: self.page_info.clone(),
+            },
+        };
+        self.pending_load = Some(load);
+        self.loading_since = Some(Instant::now());
+        self.page_title = String::from("Loading...");
+        self.status_message = self.loading_message();
+        self.status_expiry = None;
+        cancel
+    }
+
+    /// The page on screen and the load in flight, to tell later whether the tab has moved on
+    pub fn navigation_snapshot(&self) -> NavigationSnapshot {
+        NavigationSnapshot {
+            loaded_url: self.loaded_url.clone(),
+            pending: self.pending_load.as_ref().map(|load| load.cancel.clone()),
+        }
+    }
+
+    /// Whether a page has loaded, or a load started, finished or been cancelled since `snapshot`
+    pub fn has_moved_on(&self, snapshot: &NavigationSnapshot) -> bool {
+        let replaced = matches!(&snapshot.pending, Some(cancel) if cancel.is_cancelled());
+        self.loaded_url != snapshot.loaded_url
+            || self.pending_load.is_some() != snapshot.pending.is_some()
+            || replaced
+    }
+
+    /// Abandon the page request in flight and go back to showing the page that was loaded.
+    /// Returns false when nothing was loading.
+    pub fn cancel_load(&mut self) -> bool {
+        let Some(load) = self.pending_load.take() else {
+            return false;
+        };
+        load.cancel.cancel();
+        log::info!("tab {}: navigation cancelled", self.id);
+        // Following a link pushed the page being left; it's still the current one
+        if self.history.last().is_some_and(|entry| entry.url == load.previous_url) {
+            self.history.pop();
+        }
+        self.url_input = load.previous_url;
+        self.page_title = load.previous_title;
+        self.page_info = load.previous_info;
+        self.loading_since = None;
+        self.pending_scroll = None;
+        self.status_message = String::from("Navigation cancelled");
+        true
+    }
+
+    /// Display width of the line under the cursor; 0 when the page is empty or shorter
+    pub fn cursor_line_width(&self) -> usize {
+        self.rendered_content
+            .get(self.cursor_line)
+            .map_or(0, |line| line.width())
+    }
+
+    /// Pull the cursor back onto the page after its content shrank or was replaced
+    pub fn clamp_cursor(&mut self) {
+        self.cursor_line = self
+            .cursor_line
+            .min(self.rendered_content.len().saturating_sub(1));
+        self.cursor_char = self.cursor_char.min(self.cursor_line_width());
+    }
+
+    /// Keep the scroll, cursor and selected link on the page as it's rendered now.
+    /// The scroll stops where the last line reaches the bottom of the viewport.
+    pub fn clamp_view(&mut self, viewport_height: usize) {
+        let max_scroll = self.rendered_content.len().saturating_sub(viewport_height);
+        self.scroll = self.scroll.min(max_scroll);
+        self.selected_link_index = self
+            .selected_link_index
+            .min(self.link_regions.len().saturating_sub(1));
+        self.clamp_cursor();
+    }
+
+    pub fn set_mark(&mut self, name: char) {
+        let mark = Mark {
+            scroll: self.scroll,
+            cursor_line: self.cursor_line,
+            cursor_char: self.cursor_char,
+        };
+        self.marks.insert(name, mark);
+        self.set_transient_status(format!("Mark '{}' set", name));
+    }
+
+    /// Return to a saved position, clamped to the page as it's currently rendered, which a
+    /// resize or wrap change since the mark was set may have reflowed
+    pub fn jump_to_mark(&mut self, name: char, viewport_height: usize) {
+        let Some(mark) = self.marks.get(&name).copied() else {
+            self.set_transient_status(format!("Mark '{}' not set", name));
+            return;
+        };
+        self.scroll = mark.scroll;
+        self.cursor_line = mark.cursor_line;
+        self.cursor_char = mark.cursor_char;
+        self.clamp_view(viewport_height);
+    }
+
+    /// Shift the view sideways by `delta` columns, stopping once the widest line's end is visible
+    pub fn scroll_horizontally(&mut self, delta: isize, viewport_width: usize) {
+        let widest = self
+            .rendered_content
+            .iter()
+            .map(|line| line.width())
+            .max()
+            .unwrap_or(0);
+        let max_offset = widest.saturating_sub(viewport_width);
+        self.h_scroll = self.h_scroll.saturating_add_signed(delta).min(max_offset);
+    }
+
+    /// Focus the next editable form field and start typing into it
+    pub fn focus_next_input(&mut self) -> bool {
+        if self.input_regions.is_empty() {
+            return false;
         }
+        let next = match self.focused_input {
+            Some(index) => (index + 1) % self.input_regions.len(),
+            None => 0,
+        };
+        self.focus_input(next);
+        true
+    }
+
+    pub fn focus_input(&mut self, index: usize) {
+        self.focused_input = Some(index);
+        self.input_mode = InputMode::FormInput;
+        self.status_message = String::from("FORM INPUT - Type, Enter to submit, Esc to leave");
+    }
+
+    pub fn focused_field_mut(&mut self) -> Option<&mut FormField> {
+        let region = self.input_regions.get(self.focused_input?)?;
+        self.forms
+            .get_mut(region.form_index)?
+            .fields
+            .get_mut(region.field_index)
+    }
+
+    /// Show a status message that reverts to "Ready" after `STATUS_MESSAGE_TIMEOUT_MS`
+    pub fn set_transient_status(&mut self, message: impl Into<String>) {
+        let message = message.into();
+        let deadline = Instant::now() + Duration::from_millis(STATUS_MESSAGE_TIMEOUT_MS);
+        self.status_expiry = Some((deadline, message.clone()));
+        self.status_message = message;
+    }
+
+    /// Revert an expired transient message. A message set since then is left alone.
+    /// Returns true when the status line changed.
+    pub fn expire_status(&mut self, now: Instant) -> bool {
+        let mut changed = false;
+        if let Some((deadline, message)) = &self.status_expiry
+            && now >= *deadline
+        {
+            if self.status_message == *message {
+                self.status_message = String::from("Ready");
+                changed = true;
+            }
+            self.status_expiry = None;
+        }
+        changed
+    }
+
+    /// Record a page on the back stack with the current scroll, dropping the oldest entries beyond `limit`
+    pub fn push_history(&mut self, url: String, limit: usize) {
+        self.history.push(HistoryEntry {
+            url,
+            scroll: self.scroll,
+        });
+        if self.history.len() > limit {
+            let excess = self.history.len() - limit;
+            self.history.drain(..excess);
+        }
+    }
+
+    /// Byte offset of the URL bar cursor, clamped to the current input
+    fn url_cursor_byte(&self) -> usize {
+        self.url_input
+            .char_indices()
+            .nth(self.url_cursor)
+            .map(|(idx, _)| idx)
+            .unwrap_or(self.url_input.len())
+    }
+
+    pub fn move_url_cursor_to_start(&mut self) {
+        self.url_cursor = 0;
+    }
+
+    pub fn move_url_cursor_to_end(&mut self) {
+        self.url_cursor = self.url_input.chars().count();
+    }
+
+    pub fn move_url_cursor_left(&mut self) {
+        self.url_cursor = self
+            .url_cursor
+            .min(self.url_input.chars().count())
+            .saturating_sub(1);
+    }
+
+    pub fn move_url_cursor_right(&mut self) {
+        self.url_cursor = (self.url_cursor + 1).min(self.url_input.chars().count());
+    }
+
+    /// Insert text into the URL bar at the cursor and move the cursor past it
+    pub fn insert_url_text(&mut self, text: &str) {
+        let byte_idx = self.url_cursor_byte();
+        self.url_input.insert_str(byte_idx, text);
+        self.url_cursor = self.url_input[..byte_idx + text.len()].chars().count();
+    }
+
+    pub fn clear_url_input(&mut self) {
+        self.url_input.clear();
+        self.url_cursor = 0;
+    }
+
+    /// Delete the character before the URL bar cursor (Backspace)
+    pub fn delete_url_char(&mut self) {
+        let byte_idx = self.url_cursor_byte();
+        if let Some((prev_idx, _)) = self.url_input[..byte_idx].char_indices().next_back() {
+            self.url_input.replace_range(prev_idx..byte_idx, "");
+            self.url_cursor = self.url_input[..prev_idx].chars().count();
+        }
+    }
+
+    /// Delete the word before the URL bar cursor (Ctrl+W), treating URL punctuation as
+    /// word boundaries so a press removes one path segment or query parameter
+    pub fn delete_url_word(&mut self) {
+        let is_separator = |c: char| c.is_whitespace() || "/?&=#:".contains(c);
+        let byte_idx = self.url_cursor_byte();
+        let before = &self.url_input[..byte_idx];
+
+        // Skip separators directly before the cursor, then the word itself
+        let trimmed = before.trim_end_matches(is_separator);
+        let word_start = trimmed
+            .char_indices()
+            .rev()
+            .find(|(_, c)| is_separator(*c))
+            .map(|(idx, c)| idx + c.len_utf8())
+            .unwrap_or(0);
+
+        self.url_input.replace_range(word_start..byte_idx, "");
+        self.url_cursor = self.url_input[..word_start].chars().count();
     }
 
     pub fn perform_search(&mut self, query: &str) {
@@ -98,7 +477,20 @@ impl BrowserTab {
             let line_str = line.to_string();
             let line_chars: Vec<char> = line_str.chars().collect();
 
-            let mut char_idx = 0;
+            // Display column where each character starts, so matches line up with the UI
+            let mut columns = Vec::with_capacity(line_chars.len() + 1);
+            let mut col = 0;
+            for ch in &line_chars {
+                columns.push(col);
+                col += UnicodeWidthChar::width(*ch).unwrap_or(0);
+            }
+            columns.push(col);
+
+            // Line numbers in the gutter aren't part of the page
+            let mut char_idx = columns
+                .iter()
+                .position(|&column| column >= self.gutter_width)
+                .unwrap_or(line_chars.len());
             while char_idx <= line_chars.len().saturating_sub(query_chars.len()) {
                 let mut found = true;
                 for (i, &query_char) in query_chars.iter().enumerate() {
@@ -111,8 +503,8 @@ impl BrowserTab {
                 if found {
                     matches.push(crate::models::SearchMatch {
                         line_index: line_idx,
-                        start_char: char_idx,
-                        end_char: char_idx + query_chars.len(),
+                        start_char: columns[char_idx],
+                        end_char: columns[char_idx + query_chars.len()],
                     });
                     // Move past this match to avoid overlapping matches
                     char_idx += query_chars.len();
@@ -122,15 +514,19 @@ impl BrowserTab {
             }
         }
 
-        self.search_state = if matches.is_empty() {
-            None
-        } else {
-            Some(SearchState {
-                query: query.to_string(),
-                matches,
-                current_match_index: 0,
-            })
-        };
+        // The query is kept without matches so typing can go on and a re-render can re-run it
+        self.search_state = Some(SearchState {
+            query: query.to_string(),
+            matches,
+            current_match_index: 0,
+        });
+    }
+
+    /// Run the active search again after `rendered_content` was replaced (e.g. source view toggled)
+    pub fn refresh_search(&mut self) {
+        if let Some(query) = self.search_state.as_ref().map(|s| s.query.clone()) {
+            self.perform_search(&query);
+        }
     }
 
     pub fn next_search_match(&mut self) {
@@ -160,15 +556,82 @@ impl BrowserTab {
         self.status_message = String::from("Ready");
     }
 
-    pub fn initiate_download_request(&mut self, url: String) {
+    /// The rendered text a link region covers
+    pub fn link_text(&self, region: &LinkRegion) -> String {
+        let Some(line) = self.rendered_content.get(region.line_index) else {
+            return String::new();
+        };
+        let mut col = 0;
+        let mut text = String::new();
+        for ch in line.spans.iter().flat_map(|span| span.content.chars()) {
+            if col >= region.x_start && col < region.x_end {
+                text.push(ch);
+            }
+            col += UnicodeWidthChar::width(ch).unwrap_or(0);
+        }
+        text.trim().to_string()
+    }
+
+    /// Enter link filter mode: typing narrows the navigable links down to the matching ones
+    pub fn start_link_filter(&mut self) {
+        self.link_filter = Some(LinkFilter {
+            query: String::new(),
+            all_links: self.link_regions.clone(),
+            previous_selection: self.selected_link_index,
+        });
+        self.input_mode = InputMode::LinkFilter;
+        self.status_message = String::from("FILTER LINKS - Type part of a link's text or URL");
+    }
+
+    /// Keep only the links whose text or URL contains the filter query, ignoring case
+    pub fn apply_link_filter(&mut self) {
+        let Some(filter) = &self.link_filter else {
+            return;
+        };
+        let query = filter.query.to_lowercase();
+        let matches: Vec<LinkRegion> = filter
+            .all_links
+            .iter()
+            .filter(|region| {
+                region.url.to_lowercase().contains(&query)
+                    || self.link_text(region).to_lowercase().contains(&query)
+            })
+            .cloned()
+            .collect();
+        self.link_regions = matches;
+        self.selected_link_index = 0;
+        self.status_message = match self.link_regions.len() {
+            0 => String::from("No matching links"),
+            1 => String::from("1 matching link - Enter to follow"),
+            n => format!("{} matching links - Tab to cycle, Enter to follow", n),
+        };
+    }
+
+    /// Leave link filter mode with every link navigable again. When `keep_selection` is set the
+    /// link picked among the matches stays selected; otherwise the earlier selection comes back.
+    pub fn end_link_filter(&mut self, keep_selection: bool) {
+        let Some(filter) = self.link_filter.take() else {
+            return;
+        };
+        let picked = self.link_regions.get(self.selected_link_index);
+        self.selected_link_index = picked
+            .filter(|_| keep_selection)
+            .and_then(|picked| {
+                filter.all_links.iter().position(|region| {
+                    (region.line_index, region.x_start) == (picked.line_index, picked.x_start)
+                })
+            })
+            .unwrap_or(filter.previous_selection);
+        self.link_regions = filter.all_links;
+        if self.input_mode == InputMode::LinkFilter {
+            self.input_mode = InputMode::Normal;
+        }
+    }
+
+    pub fn initiate_download_request(&mut self, url: String, download_dir: &Path) {
         let raw_filename = url.split('/').last().unwrap_or("download.dat");
         let sanitized_name = crate::app::App::sanitize_filename(raw_filename);
 
-        // Resolve target directory using the directories crate
-        let download_dir = UserDirs::new()
-            .and_then(|dirs| dirs.download_dir().map(|p| p.to_path_buf()))
-            .unwrap_or_default();
-
         let target_path = download_dir.join(&sanitized_name);
         let file_exists = target_path.exists();
 
@@ -177,10 +640,44 @@ impl BrowserTab {
             filename: sanitized_name,
             target_path,
             file_exists,
+            conflict: None,
         });
     }
 }
 
+/// Coalesces bursts of terminal resize events so tabs reflow only once the size settles
+pub struct ResizeDebouncer {
+    delay: Duration,
+    pending: Option<(u16, u16)>,
+    last_event: Option<Instant>,
+}
+
+impl ResizeDebouncer {
+    pub fn new(delay: Duration) -> Self {
+        Self {
+            delay,
+            pending: None,
+            last_event: None,
+        }
+    }
+
+    /// Record a resize event, replacing any dimensions still waiting to be applied
+    pub fn record(&mut self, width: u16, height: u16, now: Instant) {
+        self.pending = Some((width, height));
+        self.last_event = Some(now);
+    }
+
+    /// Returns the latest dimensions once no resize has arrived for the debounce delay
+    pub fn take_ready(&mut self, now: Instant) -> Option<(u16, u16)> {
+        let last_event = self.last_event?;
+        if now.duration_since(last_event) < self.delay {
+            return None;
+        }
+        self.last_event = None;
+        self.pending.take()
+    }
+}
+
 pub struct App {
     pub tabs: Vec<BrowserTab>,
     pub active_tab_index: usize,
@@ -190,6 +687,30 @@ pub struct App {
     pub i2p_mode: bool,
     pub clipboard: arboard::Clipboard,
     pub network_manager: Arc<NetworkManager>,
+    /// Outlives `network_manager`, which is rebuilt when the user agent changes
+    pub throttle: Arc<HostThrottle>,
+    pub pending_count: Option<usize>,
+    /// Action waiting for the second key of a two-key sequence such as `yy` or `ma`
+    pub pending_action: Option<Action>,
+    pub keymap: Keymap,
+    pub config: Config,
+    pub downloads: BTreeMap<usize, Download>,
+    pub next_download_id: usize,
+    pub show_downloads: bool,
+    pub selected_download: usize,
+    pub launcher: Launcher,
+    pub pipe_runner: PipeRunner,
+    /// Set after an external command borrowed the terminal; the main loop clears the screen
+    pub needs_full_redraw: bool,
+    /// Something on screen may have changed since the last frame was drawn
+    pub dirty: bool,
+    pub known_hosts: Arc<KnownHosts>,
+    /// Basic auth credentials by `host:port`; never written to disk
+    pub credentials: HashMap<String, BasicCredentials>,
+    /// `host:port`s whose certificates the user chose to stop checking; never written to disk
+    pub insecure_hosts: HashSet<String>,
+    /// Second tab shown below or above the active one, when the content area is split
+    pub split: Option<SplitPane>,
 }
 
 impl App {
@@ -197,9 +718,40 @@ impl App {
         tx: mpsc::Sender<NetworkResponse>,
         rx: mpsc::Receiver<NetworkResponse>,
     ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-        let initial_tab =
+        Self::with_config(tx, rx, Config::default())
+    }
+
+    pub fn with_config(
+        tx: mpsc::Sender<NetworkResponse>,
+        rx: mpsc::Receiver<NetworkResponse>,
+        config: Config,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let mut initial_tab =
             BrowserTab::new(INITIAL_TAB_ID, String::from("https://www.rust-lang.org"));
-        let network_manager = Arc::new(NetworkManager::new()?);
+        let user_agent = config
+            .user_agent
+            .as_deref()
+            .map(resolve_user_agent)
+            .unwrap_or_else(|| String::from(USER_AGENT));
+        let request_interval = Duration::from_millis(config.request_interval_ms);
+        let throttle = Arc::new(HostThrottle::new(request_interval));
+        let network_manager = Arc::new(NetworkManager::with_settings(
+            &user_agent,
+            config.allow_https_downgrade,
+            config.allow_local_targets,
+            Arc::clone(&throttle),
+        )?);
+        let known_hosts = Arc::new(KnownHosts::load(config.gemini_known_hosts()));
+        let (keymap, keymap_problems) = match config.keymap_file() {
+            Some(path) => Keymap::load(&path),
+            None => (Keymap::default(), Vec::new()),
+        };
+        for problem in &keymap_problems {
+            log::warn!("keymap: {}", problem);
+        }
+        if !keymap_problems.is_empty() {
+            initial_tab.status_message = format!("Keymap: {}", keymap_problems.join("; "));
+        }
         Ok(Self {
             tabs: vec![initial_tab],
             active_tab_index: DEFAULT_TAB_INDEX,
@@ -209,6 +761,23 @@ impl App {
             i2p_mode: false,
             clipboard: arboard::Clipboard::new().expect("Failed to initialize clipboard"),
             network_manager,
+            throttle,
+            pending_count: None,
+            pending_action: None,
+            keymap,
+            config,
+            downloads: BTreeMap::new(),
+            next_download_id: 0,
+            show_downloads: false,
+            selected_download: 0,
+            launcher: system_launcher(),
+            pipe_runner: system_pipe_runner(),
+            needs_full_redraw: false,
+            dirty: true,
+            known_hosts,
+            credentials: HashMap::new(),
+            insecure_hosts: HashSet::new(),
+            split: None,
         })
     }
 
@@ -216,33 +785,533 @@ impl App {
         &mut self.tabs[self.active_tab_index]
     }
 
+    /// Called from the main loop so transient status messages time out
+    pub fn expire_status_messages(&mut self, now: Instant) {
+        for tab in &mut self.tabs {
+            if tab.expire_status(now) {
+                self.dirty = true;
+            }
+        }
+    }
+
+    /// Whether a spinner is on screen, which needs frames even when nothing else happens
+    pub fn is_animating(&self) -> bool {
+        if !self.config.animations {
+            return false;
+        }
+        let downloading_unknown_size = self.tabs[self.active_tab_index]
+            .download_state
+            .as_ref()
+            .is_some_and(|d| d.status == DownloadStatus::Active && d.total_size.is_none());
+        downloading_unknown_size || self.tabs.iter().any(|tab| tab.loading_since.is_some())
+    }
+
+    /// Whether a page is loading or a download is running, whose messages shouldn't wait for a
+    /// long poll interval to pass
+    pub fn is_busy(&self) -> bool {
+        self.tabs.iter().any(|tab| tab.loading_since.is_some())
+            || self
+                .downloads
+                .values()
+                .any(|download| download.status == DownloadStatus::Active)
+    }
+
+    /// Called once per main loop iteration: true when a frame should be drawn, which clears
+    /// the dirty flag. Idle with nothing animating, nothing is redrawn.
+    pub fn should_redraw(&mut self) -> bool {
+        let redraw = self.dirty || self.is_animating();
+        self.dirty = false;
+        redraw
+    }
+
+    pub fn toggle_download_manager(&mut self) {
+        self.show_downloads = !self.show_downloads;
+        self.clamp_selected_download();
+    }
+
+    pub fn select_next_download(&mut self) {
+        if self.selected_download + 1 < self.downloads.len() {
+            self.selected_download += 1;
+        }
+    }
+
+    pub fn select_prev_download(&mut self) {
+        self.selected_download = self.selected_download.saturating_sub(1);
+    }
+
+    /// Cancel the highlighted download if it's running, otherwise remove it from the list
+    pub fn remove_selected_download(&mut self) {
+        let Some((id, finished)) = self
+            .downloads
+            .values()
+            .nth(self.selected_download)
+            .map(|d| (d.id, d.is_finished()))
+        else {
+            return;
+        };
+        if finished {
+            self.downloads.remove(&id);
+            self.clamp_selected_download();
+        } else {
+            self.cancel_download(id);
+        }
+    }
+
+    /// Stop an active download; its task exits without reporting further progress
+    pub fn cancel_download(&mut self, download_id: usize) {
+        let Some(download) = self.downloads.get_mut(&download_id) else {
+            return;
+        };
+        if download.is_finished() {
+            return;
+        }
+        download.cancel_token.cancel();
+        download.status = DownloadStatus::Cancelled;
+        let entry = download.clone();
+
+        if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == entry.tab_id) {
+            // Dismiss an overwrite prompt still waiting on this download
+            if tab
+                .download_prompt
+                .as_ref()
+                .and_then(|p| p.conflict.as_ref())
+                .is_some_and(|c| c.download_id == download_id)
+            {
+                tab.download_prompt = None;
+            }
+            tab.download_state = Some(entry);
+            tab.status_message = String::from("Download cancelled");
+        }
+    }
+
+    /// Open a finished download with the system's default application
+    pub fn open_download(&mut self, download_id: usize) {
+        let path = match self.downloads.get(&download_id) {
+            Some(Download {
+                status: DownloadStatus::Completed,
+                saved_path: Some(path),
+                ..
+            }) => path.clone(),
+            _ => {
+                self.current_tab().set_transient_status("Only completed downloads can be opened");
+                return;
+            }
+        };
+        let result = (self.launcher)(open_command(Path::new(&path)));
+        self.current_tab().set_transient_status(match result {
+            Ok(()) => format!("Opened {}", path),
+            Err(e) => format!("Couldn't open {}: {}", path, e),
+        });
+    }
+
+    pub fn open_selected_download(&mut self) {
+        if let Some(id) = self.downloads.keys().nth(self.selected_download).copied() {
+            self.open_download(id);
+        }
+    }
+
+    /// Follow a link from the page: navigate to it or offer to download it.
+    /// With `head_check_links` on, ambiguous links are classified by a HEAD request first.
+    pub fn follow_link(&mut self, url: String) {
+        if gemini::is_gemini_url(&url) {
+            self.open_link(url, false);
+            return;
+        }
+        if self.config.head_check_links && is_ambiguous_link(&url) {
+            let tab_id = self.current_tab().id;
+            let tx = self.tx.clone();
+            let nm = Arc::clone(&self.network_manager);
+            let use_i2p = self.i2p_mode;
+            // A blocked target isn't probed; the extension heuristic decides instead
+            let blocked = self.blocked_target(&url);
+            let snapshot = self.current_tab().navigation_snapshot();
+            self.current_tab().status_message = String::from("Checking link...");
+            tokio::spawn(async move {
+                let verdict = match blocked {
+                    Some(reason) => {
+                        log::warn!("tab {}: HEAD check skipped: {}", tab_id, reason);
+                        None
+                    }
+                    None => probe_link_is_download(nm.get_client(use_i2p), &url).await,
+                };
+                let checked = NetworkResponse::LinkChecked(tab_id, url, verdict, snapshot);
+                let _ = tx.send(checked).await;
+            });
+            return;
+        }
+        let is_download = is_downloadable_file(&url);
+        self.open_link_from_page(url, is_download);
+    }
+
+    /// Like `open_link`, for a link followed from the page on screen, which may be the Referer
+    pub fn open_link_from_page(&mut self, url: String, is_download: bool) {
+        if !is_download {
+            self.current_tab().referer = self.link_referer(&url);
+        }
+        self.open_link(url, is_download);
+    }
+
+    /// What following `url` from the current page sends as its Referer, if `send_referer` is on.
+    /// A `rel="noreferrer"` link to that address anywhere on the page means none.
+    fn link_referer(&self, url: &str) -> Option<String> {
+        if !self.config.send_referer {
+            return None;
+        }
+        let tab = &self.tabs[self.active_tab_index];
+        let no_referrer = tab
+            .link_regions
+            .iter()
+            .any(|region| region.no_referrer && tab.resolve_link(&region.url) == url);
+        if no_referrer {
+            return None;
+        }
+        crate::network::referer_for(&tab.loaded_url, url)
+    }
+
+    /// Return to the previous page on the back stack, landing where it was left
+    pub fn go_back(&mut self) {
+        let Some(entry) = self.current_tab().history.pop() else {
+            return;
+        };
+        self.current_tab().url_input = entry.url;
+        self.submit_request();
+        self.current_tab().pending_scroll = Some(entry.scroll);
+    }
+
+    /// Either prompt to download `url` or navigate the current tab to it
+    pub fn open_link(&mut self, url: String, is_download: bool) {
+        if is_download {
+            let download_dir = self.config.download_dir();
+            self.current_tab().initiate_download_request(url, &download_dir);
+            return;
+        }
+        let limit = self.config.max_history;
+        let tab = self.current_tab();
+        if !tab.url_input.is_empty() {
+            tab.push_history(tab.url_input.clone(), limit);
+        }
+        tab.url_input = url;
+        self.submit_request();
+        self.current_tab().selected_link_index = 0;
+    }
+
+    /// Navigate one path segment up from the current page
+    pub fn go_to_parent(&mut self) {
+        match parent_url(&self.current_tab().url_input) {
+            Some(url) => self.open_link(url, false),
+            None => self.current_tab().set_transient_status("Already at the top level"),
+        }
+    }
+
+    /// Load the page behind the current certificate warning without checking the certificate,
+    /// and keep not checking it for that host until the browser exits
+    pub fn trust_certificate(&mut self) {
+        let Some(host) = self.current_tab().certificate_host.clone() else {
+            self.current_tab().set_transient_status("No certificate problem on this page");
+            return;
+        };
+        if !self.config.allow_insecure_retry {
+            self.current_tab()
+                .set_transient_status("Loading it anyway is turned off (allow_insecure_retry)");
+            return;
+        }
+        log::warn!("certificate checks turned off for {}", host);
+        self.insecure_hosts.insert(host);
+        self.submit_request();
+    }
+
+    /// Load the feed the current page advertises
+    pub fn open_feed(&mut self) {
+        match self.current_tab().feed_url.clone() {
+            Some(url) => self.open_link(url, false),
+            None => self.current_tab().set_transient_status("No feed on this page"),
+        }
+    }
+
+    /// Answer the overwrite prompt of the current tab's waiting download
+    pub fn resolve_download_conflict(&mut self, choice: ConflictChoice) {
+        let Some(conflict) = self
+            .current_tab()
+            .download_prompt
+            .take()
+            .and_then(|p| p.conflict)
+        else {
+            return;
+        };
+        let _ = conflict.reply.send(choice);
+        if choice == ConflictChoice::Cancel {
+            self.cancel_download(conflict.download_id);
+        } else {
+            self.current_tab().status_message = String::from("Downloading...");
+        }
+    }
+
+    /// Drop every completed or failed entry, keeping active downloads
+    pub fn clear_finished_downloads(&mut self) {
+        self.downloads.retain(|_, d| !d.is_finished());
+        self.clamp_selected_download();
+    }
+
+    fn clamp_selected_download(&mut self) {
+        self.selected_download = self
+            .selected_download
+            .min(self.downloads.len().saturating_sub(1));
+    }
+
     pub fn render_tab(&mut self, tab_index: usize, width: u16) {
+        let max_links = self.config.max_link_regions;
+        let number_headings = self.config.number_headings;
+        let tab_width = self.config.tab_width;
         if let Some(tab) = self.tabs.get_mut(tab_index) {
             let content_width = (width as usize).saturating_sub(2);
+            // A narrower preferred column leaves the rest of the pane empty
+            let content_width = tab.column_width.map_or(content_width, |columns| {
+                content_width.min(columns + UI_BORDER_WIDTH)
+            });
+            tab.gutter_width = 0;
             if tab.is_source_view {
-                tab.rendered_content = tab
-                    .html_source
-                    .lines()
-                    .map(|l| Line::from(l.to_string()))
-                    .collect();
+                let numbered = add_line_numbers(highlight_html_source(&tab.html_source));
+                // Digits of the last line number and the space after them
+                let gutter = numbered.len().to_string().len() + 1;
+                tab.gutter_width = gutter;
+                tab.rendered_content = if tab.wrap_lines {
+                    // Continuation rows start after the line number gutter
+                    wrap_lines(numbered, content_width, gutter)
+                } else {
+                    numbered
+                };
                 tab.link_regions.clear();
+                tab.input_regions.clear();
+            } else if let Some(delimiter) = tab.table_delimiter {
+                // Tables keep their columns aligned, so they're never wrapped
+                let rows = parse_delimited(&tab.html_source, delimiter);
+                tab.rendered_content = table_lines(&rows, true);
+                tab.link_regions.clear();
+                tab.input_regions.clear();
+                tab.forms.clear();
             } else {
                 let document = Html::parse_document(&tab.html_source);
                 let mut renderer = DomRenderer::new(content_width);
-                renderer.render(&document);
+                renderer.set_max_links(max_links);
+                renderer.set_wrap(tab.wrap_lines);
+                renderer.set_heading_numbers(number_headings);
+                renderer.set_tab_width(tab_width);
+                renderer.set_form_values(std::mem::take(&mut tab.forms));
+                if tab.reader_mode {
+                    renderer.render_reader(&document);
+                } else {
+                    renderer.render(&document);
+                }
                 tab.rendered_content = renderer.lines;
                 tab.link_regions = renderer.links;
+                tab.forms = renderer.forms;
+                tab.input_regions = renderer.inputs;
+                // Reflowing moves every link, so filter the new positions
+                if let Some(filter) = &mut tab.link_filter {
+                    filter.all_links = std::mem::take(&mut tab.link_regions);
+                    tab.apply_link_filter();
+                }
             }
         }
     }
 
+    /// Retry the page behind the current tab's login prompt with the typed credentials
+    pub fn submit_credentials(&mut self) {
+        let Some(prompt) = self.current_tab().auth_prompt.take() else {
+            return;
+        };
+        self.credentials.insert(
+            prompt.host,
+            BasicCredentials {
+                username: prompt.username,
+                password: prompt.password,
+            },
+        );
+        self.current_tab().url_input = prompt.url;
+        self.submit_request();
+    }
+
+    /// Submit a GET form: its fields become the query string of the resolved `action` URL
+    pub fn submit_form(&mut self, form_index: usize) {
+        let tab = self.current_tab();
+        tab.input_mode = InputMode::Normal;
+        tab.focused_input = None;
+        let Some(form) = tab.forms.get(form_index) else {
+            return;
+        };
+        if form.method != "get" {
+            tab.set_transient_status("Only GET forms can be submitted");
+            return;
+        }
+
+        let action = if form.action.is_empty() {
+            tab.url_input.clone()
+        } else {
+            tab.resolve_link(&form.action)
+        };
+        let query = form
+            .fields
+            .iter()
+            .filter(|field| !field.name.is_empty())
+            .fold(
+                url::form_urlencoded::Serializer::new(String::new()),
+                |mut query, field| {
+                    query.append_pair(&field.name, &field.value);
+                    query
+                },
+            )
+            .finish();
+
+        // GET submission replaces any query already on the action URL
+        let target = match Url::parse(&action) {
+            Ok(mut url) => {
+                url.set_query(Some(&query));
+                url.to_string()
+            }
+            Err(_) => format!("{}?{}", action.split('?').next().unwrap_or(""), query),
+        };
+        self.open_link(target, false);
+    }
+
+    /// Switch reader mode for the current tab and render the page again from its source
+    pub fn toggle_reader_mode(&mut self, width: u16) {
+        let index = self.active_tab_index;
+        let tab = self.current_tab();
+        tab.reader_mode = !tab.reader_mode;
+        tab.scroll = 0;
+        tab.h_scroll = 0;
+        tab.selected_link_index = 0;
+        tab.focused_input = None;
+        self.render_tab(index, width);
+        let tab = self.current_tab();
+        tab.status_message = if tab.reader_mode {
+            String::from("Reader mode on")
+        } else {
+            String::from("Reader mode off")
+        };
+    }
+
+    /// Flip soft wrapping for the current tab and reflow it
+    pub fn toggle_wrap(&mut self, width: u16) {
+        let index = self.active_tab_index;
+        let tab = self.current_tab();
+        tab.wrap_lines = !tab.wrap_lines;
+        tab.h_scroll = 0;
+        self.render_tab(index, width);
+        let tab = self.current_tab();
+        tab.scroll = tab.scroll.min(tab.rendered_content.len().saturating_sub(1));
+        tab.status_message = if tab.wrap_lines {
+            String::from("Wrap on")
+        } else {
+            String::from("Wrap off - H/L scroll sideways")
+        };
+    }
+
+    /// Make the current tab's text column `delta` columns wider (narrower when negative) and
+    /// reflow it. Widening it to the terminal's width goes back to filling the terminal.
+    pub fn adjust_column_width(&mut self, delta: isize, width: u16) {
+        let full = (width as usize).saturating_sub(2 + UI_BORDER_WIDTH);
+        let index = self.active_tab_index;
+        let tab = self.current_tab();
+        let current = tab.column_width.map_or(full, |columns| columns.min(full));
+        let columns = current.saturating_add_signed(delta).max(MIN_COLUMN_WIDTH);
+        tab.column_width = (columns < full).then_some(columns);
+        self.render_tab(index, width);
+        let tab = self.current_tab();
+        tab.scroll = tab.scroll.min(tab.rendered_content.len().saturating_sub(1));
+        tab.status_message = match tab.column_width {
+            Some(columns) => format!("Column width {}", columns),
+            None => String::from("Full width"),
+        };
+    }
+
+    /// Let the current tab's text fill the terminal again
+    pub fn reset_column_width(&mut self, width: u16) {
+        let index = self.active_tab_index;
+        self.current_tab().column_width = None;
+        self.render_tab(index, width);
+        let tab = self.current_tab();
+        tab.scroll = tab.scroll.min(tab.rendered_content.len().saturating_sub(1));
+        tab.status_message = String::from("Full width");
+    }
+
     pub fn resize_all_tabs(&mut self, width: u16) {
         for i in 0..self.tabs.len() {
             self.render_tab(i, width);
         }
+        self.dirty = true;
+    }
+
+    /// True (after telling the user) when no more tabs may be opened
+    fn tab_limit_reached(&mut self) -> bool {
+        if self.tabs.len() < self.config.max_tabs {
+            return false;
+        }
+        let limit = self.config.max_tabs;
+        self.current_tab()
+            .set_transient_status(format!("Tab limit reached ({} tabs)", limit));
+        true
+    }
+
+    /// Open the pages named on the command line and start fetching them: the first in the
+    /// initial tab, the rest in tabs of their own. The first stays in front.
+    pub fn open_startup_urls(&mut self, urls: Vec<String>) {
+        let mut urls = urls.into_iter();
+        let Some(first) = urls.next() else {
+            return;
+        };
+        self.current_tab().url_input = first;
+        self.submit_request();
+        for url in urls {
+            if self.tab_limit_reached() {
+                break;
+            }
+            self.add_tab(Some(url));
+            self.submit_request();
+        }
+        self.active_tab_index = DEFAULT_TAB_INDEX;
+    }
+
+    /// The open tabs and their column widths, to be reopened by `restore_session`
+    pub fn session(&self) -> Session {
+        let mut session = Session::default();
+        for (index, tab) in self.tabs.iter().enumerate() {
+            // A page still loading is saved as the one asked for, not the one it replaces
+            let url = if tab.pending_load.is_some() || tab.loaded_url.is_empty() {
+                &tab.url_input
+            } else {
+                &tab.loaded_url
+            };
+            if url.is_empty() {
+                continue;
+            }
+            if index == self.active_tab_index {
+                session.active = session.tabs.len();
+            }
+            session.tabs.push(SessionTab {
+                url: url.clone(),
+                column_width: tab.column_width,
+            });
+        }
+        session
+    }
+
+    /// Reopen a saved session's tabs like startup URLs, each with its saved column width
+    pub fn restore_session(&mut self, session: Session) {
+        let urls = session.tabs.iter().map(|tab| tab.url.clone()).collect();
+        self.open_startup_urls(urls);
+        for (tab, saved) in self.tabs.iter_mut().zip(&session.tabs) {
+            tab.column_width = saved.column_width;
+        }
+        if session.active < self.tabs.len() {
+            self.active_tab_index = session.active;
+        }
     }
 
     pub fn add_tab(&mut self, url: Option<String>) {
+        if self.tab_limit_reached() {
+            return;
+        }
         let start_url = url.unwrap_or_else(|| String::from("https://www.rust-lang.org"));
         let new_tab = BrowserTab::new(self.id_counter, start_url);
         self.tabs.push(new_tab);
@@ -250,39 +1319,236 @@ impl App {
         self.id_counter += 1;
     }
 
+    /// Make tab `index` the active one. Its content may have shrunk since it was last shown,
+    /// by a reflow or a shorter page, so its view is pulled back onto it.
+    /// Selecting the tab shown in the other pane of a split view moves the focus there.
+    pub fn select_tab(&mut self, index: usize, terminal_height: u16) {
+        if index >= self.tabs.len() {
+            return;
+        }
+        if self.split_pane().is_some_and(|split| split.tab_index == index) {
+            self.switch_pane();
+        } else {
+            self.active_tab_index = index;
+        }
+        let viewport_height = self.viewport_height(terminal_height);
+        self.current_tab().clamp_view(viewport_height);
+    }
+
     pub fn close_tab(&mut self) {
         if self.tabs.len() > 1 {
-            self.tabs.remove(self.active_tab_index);
+            let closed = self.active_tab_index;
+            self.tabs.remove(closed);
             if self.active_tab_index >= self.tabs.len() {
                 self.active_tab_index = self.tabs.len() - 1;
             }
+            if let Some(split) = &mut self.split {
+                if split.tab_index > closed {
+                    split.tab_index -= 1;
+                }
+                // The other pane's tab took the closed one's place, so there's one pane left
+                if split.tab_index == self.active_tab_index {
+                    self.split = None;
+                }
+            }
+        }
+    }
+
+    /// Close every tab except the active one
+    pub fn close_other_tabs(&mut self) {
+        if self.tabs.len() > 1 {
+            let keep = self.tabs.swap_remove(self.active_tab_index);
+            self.tabs = vec![keep];
+            self.active_tab_index = 0;
+            self.split = None;
         }
     }
 
+    /// Close all tabs after the active one
+    pub fn close_tabs_to_right(&mut self) {
+        self.tabs.truncate(self.active_tab_index + 1);
+        if self.split.is_some_and(|split| split.tab_index >= self.tabs.len()) {
+            self.split = None;
+        }
+    }
+
+    /// Split the content area to show a second tab below the active one, or go back to a
+    /// single pane. With only one tab, the selected link opens in a new tab for the lower pane.
+    pub fn toggle_split(&mut self) {
+        if self.split.take().is_some() {
+            return;
+        }
+        let other = if self.tabs.len() > 1 {
+            (self.active_tab_index + 1) % self.tabs.len()
+        } else {
+            let tab = &self.tabs[self.active_tab_index];
+            let Some(region) = tab.link_regions.get(tab.selected_link_index) else {
+                self.current_tab().set_transient_status("Split view needs a second tab");
+                return;
+            };
+            let url = tab.resolve_link(&region.url);
+            let tab_count = self.tabs.len();
+            self.open_link_in_background_tab(url);
+            if self.tabs.len() == tab_count {
+                return; // Not a page link, or no room for another tab
+            }
+            self.current_tab().set_transient_status("Opened link in split view");
+            self.tabs.len() - 1
+        };
+        self.split = Some(SplitPane { tab_index: other, above: false });
+    }
+
+    /// Move the focus to the other pane of a split view
+    pub fn switch_pane(&mut self) {
+        if let Some(split) = self.split_pane() {
+            self.split = Some(SplitPane {
+                tab_index: self.active_tab_index,
+                above: !split.above,
+            });
+            self.active_tab_index = split.tab_index;
+        }
+    }
+
+    /// The other pane of a split view, if there is one showing a different tab
+    pub fn split_pane(&self) -> Option<SplitPane> {
+        self.split.filter(|split| {
+            split.tab_index < self.tabs.len() && split.tab_index != self.active_tab_index
+        })
+    }
+
+    /// Where the focused pane and, in a split view, the other one sit within the content
+    /// area. The upper pane gets the smaller half.
+    pub fn pane_areas(&self, content: Rect) -> (Rect, Option<Rect>) {
+        let Some(split) = self.split_pane() else {
+            return (content, None);
+        };
+        let upper = Rect { height: content.height / 2, ..content };
+        let lower = Rect {
+            y: content.y + upper.height,
+            height: content.height - upper.height,
+            ..content
+        };
+        if split.above { (lower, Some(upper)) } else { (upper, Some(lower)) }
+    }
+
+    /// `pane_areas` for the whole content area of a terminal of the given size
+    pub fn panes(&self, terminal_width: u16, terminal_height: u16) -> (Rect, Option<Rect>) {
+        let chrome = TAB_BAR_HEIGHT + URL_BAR_HEIGHT;
+        let content = Rect::new(0, chrome, terminal_width, terminal_height.saturating_sub(chrome));
+        self.pane_areas(content)
+    }
+
+    /// Lines of page the focused pane shows at once
+    pub fn viewport_height(&self, terminal_height: u16) -> usize {
+        // Inside the pane's top and bottom borders
+        self.panes(0, terminal_height).0.height.saturating_sub(2) as usize
+    }
+
+    /// Put a link's target on the clipboard instead of following it
+    pub fn copy_link_url(&mut self, url: String) {
+        let message = match self.clipboard.set_text(url.clone()) {
+            Ok(_) => format!("Link copied: {}", url),
+            Err(e) => format!("Couldn't copy link: {}", e),
+        };
+        self.current_tab().set_transient_status(message);
+    }
+
+    /// Handle links that can't be fetched over HTTP. Returns true when the link was consumed,
+    /// in which case no navigation should happen.
+    pub fn handle_non_web_link(&mut self, url: &str) -> bool {
+        let lower = url.trim().to_lowercase();
+        if lower.starts_with("javascript:") {
+            self.current_tab().set_transient_status("JavaScript links aren't supported");
+            return true;
+        }
+
+        let kind = if lower.starts_with("mailto:") {
+            "Email address"
+        } else if lower.starts_with("tel:") {
+            "Phone number"
+        } else {
+            return false;
+        };
+
+        // Copy just the address/number, without the scheme or any ?subject= style query
+        let target = url.trim().split_once(':').map(|(_, rest)| rest).unwrap_or("");
+        let target = target.split('?').next().unwrap_or("").to_string();
+        let message = match self.clipboard.set_text(target.clone()) {
+            Ok(_) => format!("{} copied to clipboard: {}", kind, target),
+            Err(_) => format!("{}: {}", kind, target),
+        };
+        self.current_tab().set_transient_status(message);
+        true
+    }
+
     pub fn open_link_in_new_tab(&mut self, url: String) {
-        let new_tab = BrowserTab::new(self.id_counter, url);
+        self.open_link_in_tab(url, true);
+    }
+
+    /// Opens the link in a new tab that loads while focus stays on the current one
+    pub fn open_link_in_background_tab(&mut self, url: String) {
+        self.open_link_in_tab(url, false);
+    }
+
+    fn open_link_in_tab(&mut self, url: String, focus: bool) {
+        if self.handle_non_web_link(&url) || self.tab_limit_reached() {
+            return;
+        }
+        let previous_tab = self.active_tab_index;
+        let referer = self.link_referer(&url);
+        let mut new_tab = BrowserTab::new(self.id_counter, url);
+        new_tab.referer = referer;
         self.tabs.push(new_tab);
         self.active_tab_index = self.tabs.len() - 1;
         self.id_counter += 1;
+        // The request is sent for whichever tab is active, so switch back only afterwards
         self.submit_request();
+        if !focus {
+            self.active_tab_index = previous_tab;
+            self.current_tab().set_transient_status("Opened link in background tab");
+        }
     }
 
     pub fn submit_request(&mut self) {
+        self.current_tab().pending_scroll = None;
+        // Taken up front so a request that never goes out doesn't leave it for the next one
+        let referer = self.current_tab().referer.take();
+        let typed_url = self.current_tab().url_input.clone();
+        if self.handle_non_web_link(&typed_url) {
+            return;
+        }
+
+        if gemini::is_gemini_url(&typed_url) {
+            self.submit_gemini_request(typed_url.trim().to_string());
+            return;
+        }
+
+        if about::is_about_url(&typed_url) {
+            self.submit_internal_page(typed_url.trim().to_string());
+            return;
+        }
+
         let use_i2p = self.i2p_mode;
+        let tracking_params = if self.config.strip_tracking_params {
+            self.config.tracking_params.clone()
+        } else {
+            Vec::new()
+        };
         let tab = self.current_tab();
         let mut target_url = tab.url_input.clone();
 
-        // URL Normalization
+        // URL Normalization: anything that doesn't look like an address is a search
         if !target_url.starts_with("http://") && !target_url.starts_with("https://") {
-            if target_url.contains('.') && !target_url.contains(' ') {
-                target_url = if target_url.ends_with(".i2p") {
-                    format!("http://{}", target_url)
-                } else {
-                    format!("https://{}", target_url)
-                };
+            let typed = target_url.trim().to_string();
+            if looks_like_address(&typed) {
+                // I2P sites are plain HTTP; the proxy's tunnels already encrypt the traffic
+                target_url = format!("http://{}", typed);
+                if !is_i2p_url(&target_url) {
+                    target_url = format!("https://{}", typed);
+                }
             } else {
                 let safe_query = url::form_urlencoded::Serializer::new(String::new())
-                    .append_pair("query", &target_url)
+                    .append_pair("query", &typed)
                     .finish();
                 target_url = format!("{}{}", MARGINALIA_SEARCH_URL, safe_query);
             }
@@ -311,66 +1577,313 @@ impl App {
             }
         }
 
+        // Unicode domains are sent (and shown while editing) in their punycode form
+        let target_url = crate::network::encode_idn_url(&target_url);
+
+        // Drop tracking parameters so the fetched URL and later history entries stay clean
+        let target_url = crate::network::strip_tracking_params(&target_url, &tracking_params);
+
         tab.url_input = target_url.clone();
         let id = tab.id;
+        log::info!("tab {}: GET {}{}", id, target_url, if use_i2p { " via I2P" } else { "" });
         let tx_clone = self.tx.clone();
+        let max_page_size = self.config.max_page_size;
+        // Without remembering, credentials entered at the prompt are good for this one request
+        let credentials = auth_host_key(&target_url).and_then(|key| {
+            if self.config.remember_credentials {
+                self.credentials.get(&key).cloned()
+            } else {
+                self.credentials.remove(&key)
+            }
+        });
         let use_i2p = self.i2p_mode;
         let network_manager = Arc::clone(&self.network_manager);
+        let blocked = self.blocked_target(&target_url);
 
         let domain_for_jump = Url::parse(&target_url)
             .ok()
             .and_then(|u| u.domain().map(|s| s.to_string()))
             .unwrap_or_default();
+        let jump_services = self.config.jump_services.clone();
+        let insecure = self.config.allow_insecure_retry
+            && auth_host_key(&target_url).is_some_and(|key| self.insecure_hosts.contains(&key));
+        let jump_timeout = Duration::from_millis(self.config.jump_timeout_ms);
+        let host = Url::parse(&target_url)
+            .ok()
+            .and_then(|u| u.host_str().map(|s| s.to_string()))
+            .unwrap_or_default();
+        let phase = move |stage: &str, host: &str| {
+            NetworkResponse::Info(id, format!("{} {}...", stage, host))
+        };
+
+        let cancel = self.current_tab().begin_load();
 
         tokio::spawn(async move {
-            let _ = tx_clone.send(NetworkResponse::Loading(id)).await;
+            let fetch = async move {
+                let _ = tx_clone.send(NetworkResponse::Loading(id)).await;
 
-            let client = network_manager.get_client(use_i2p);
-            let mut resp_result = client.get(&target_url).send().await;
+                // Waiting for the host's request interval isn't shown as a connection phase
+                network_manager.throttle().wait(&target_url).await;
 
-            if let Ok(ref resp) = resp_result {
-                if resp.status() == StatusCode::INTERNAL_SERVER_ERROR
-                    || resp.status() == StatusCode::SERVICE_UNAVAILABLE
+                // The name is looked up, and checked, while connecting, so these two phases
+                // pass together
+                let _ = tx_clone.send(phase("Resolving", &host)).await;
+                if let Some(reason) = blocked {
+                    log::warn!("tab {}: {}", id, reason);
+                    let _ = tx_clone.send(NetworkResponse::Error(id, reason)).await;
+                    return;
+                }
+
+                let _ = tx_clone.send(phase("Connecting to", &host)).await;
+                let client = if insecure {
+                    network_manager.insecure_client(use_i2p)
+                } else {
+                    network_manager.get_client(use_i2p)
+                };
+                let started = Instant::now();
+                let mut request = client.get(&target_url);
+                if let Some(referer) = &referer {
+                    request = request.header(reqwest::header::REFERER, referer);
+                }
+                if let Some(credentials) = &credentials {
+                    request =
+                        request.basic_auth(&credentials.username, Some(&credentials.password));
+                }
+                let mut resp_result = request.send().await;
+
+                if let Ok(ref resp) = resp_result
+                    && resp.status() == StatusCode::UNAUTHORIZED
+                    && let Some(realm) = resp
+                        .headers()
+                        .get(reqwest::header::WWW_AUTHENTICATE)
+                        .and_then(|value| value.to_str().ok())
+                        .and_then(basic_auth_realm)
                 {
-                    if let Ok(jump_resp) =
-                        attempt_jump(&client, &domain_for_jump, tx_clone.clone(), id).await
-                    {
-                        resp_result = Ok(jump_resp);
-                    }
+                    let _ = tx_clone
+                        .send(NetworkResponse::AuthRequired(id, target_url, realm))
+                        .await;
+                    return;
                 }
-            }
 
-            match resp_result {
-                Ok(resp) => {
-                    if let Some(len) = resp.content_length() {
-                        if len > MAX_PAGE_SIZE_BYTES {
+                // Unknown I2P names get a server error from the proxy, or fail to connect at all.
+                // Other sites' server errors are their own and never go to jump services.
+                let unresolved = is_i2p_url(&target_url)
+                    && match &resp_result {
+                        Ok(resp) => {
+                            resp.status() == StatusCode::INTERNAL_SERVER_ERROR
+                                || resp.status() == StatusCode::SERVICE_UNAVAILABLE
+                        }
+                        Err(e) => e.is_connect(),
+                    };
+                if unresolved && !domain_for_jump.is_empty() && !jump_services.is_empty() {
+                    let jumped = attempt_jump(
+                        &network_manager,
+                        use_i2p,
+                        &jump_services,
+                        jump_timeout,
+                        &domain_for_jump,
+                        tx_clone.clone(),
+                        id,
+                    )
+                    .await;
+                    match jumped {
+                        // Jump services redirect to the resolved address; it becomes the final URL
+                        Ok(jump_resp) => resp_result = Ok(jump_resp),
+                        // A proxy that answered keeps its error page; only a failed connection
+                        // gets the failure page, as there's nothing else to show
+                        Err(attempts) if resp_result.is_ok() => {
+                            log::info!(
+                                "tab {}: no jump service knew {} ({} tried)",
+                                id,
+                                domain_for_jump,
+                                attempts.len()
+                            );
+                        }
+                        Err(attempts) => {
                             let _ = tx_clone
-                                .send(NetworkResponse::Error(id, "Page too large".to_string()))
+                                .send(NetworkResponse::JumpFailed(
+                                    id,
+                                    target_url,
+                                    domain_for_jump,
+                                    attempts,
+                                ))
                                 .await;
                             return;
                         }
                     }
+                }
 
-                    match resp.text().await {
-                        Ok(html_text) => {
-                            let metadata = parse_html_metadata(&html_text);
-                            let _ = tx_clone
-                                .send(NetworkResponse::Success(id, metadata.title, html_text))
-                                .await;
+                match resp_result {
+                    Ok(resp) => {
+                        let info = page_info_from_response(&resp);
+                        let _ = tx_clone.send(NetworkResponse::ResponseInfo(id, info)).await;
+
+                        if resp.content_length().is_some_and(|len| len > max_page_size) {
+                            let too_large =
+                                NetworkResponse::PageTooLarge(id, target_url, max_page_size);
+                            let _ = tx_clone.send(too_large).await;
+                            return;
                         }
-                        Err(e) => {
-                            let _ = tx_clone
-                                .send(NetworkResponse::Error(id, e.to_string()))
-                                .await;
+
+                        let content_type = resp
+                            .headers()
+                            .get(reqwest::header::CONTENT_TYPE)
+                            .and_then(|v| v.to_str().ok())
+                            .map(|v| v.to_string());
+                        let final_url = resp.url().to_string();
+                        // Named after the host that answered, which redirects may have changed
+                        let final_host = resp.url().host_str().unwrap_or_default().to_string();
+                        let _ = tx_clone.send(phase("Downloading", &final_host)).await;
+                        match read_body_limited(resp, max_page_size).await {
+                            Ok(Some(body)) => {
+                                let mut html_text = decode_body(&body, content_type.as_deref());
+                                if is_markdown(content_type.as_deref(), &final_url) {
+                                    html_text = markdown_to_html(&html_text);
+                                } else if is_xml(content_type.as_deref(), &final_url)
+                                    && let Some(listing) = sitemap_to_html(&html_text)
+                                        .or_else(|| feed_to_html(&html_text))
+                                {
+                                    html_text = listing;
+                                }
+                                let elapsed = started.elapsed();
+                                let metadata = parse_html_metadata(&html_text);
+                                let _ = tx_clone
+                                    .send(NetworkResponse::Success(
+                                        id,
+                                        final_url,
+                                        metadata.title,
+                                        html_text,
+                                        elapsed,
+                                    ))
+                                    .await;
+                            }
+                            Ok(None) => {
+                                let too_large =
+                                    NetworkResponse::PageTooLarge(id, target_url, max_page_size);
+                                let _ = tx_clone.send(too_large).await;
+                            }
+                            Err(e) => {
+                                let _ = tx_clone
+                                    .send(NetworkResponse::Error(id, describe_fetch_error(&e)))
+                                    .await;
+                            }
                         }
                     }
+                    Err(e) => {
+                        let failed = match certificate_problem(&e) {
+                            Some(problem) => {
+                                NetworkResponse::CertificateError(id, target_url, problem)
+                            }
+                            None => NetworkResponse::Error(id, describe_fetch_error(&e)),
+                        };
+                        let _ = tx_clone.send(failed).await;
+                    }
                 }
-                Err(e) => {
-                    let _ = tx_clone
-                        .send(NetworkResponse::Error(id, e.to_string()))
-                        .await;
-                }
+            };
+            tokio::select! {
+                biased;
+                _ = cancel.cancelled() => log::debug!("tab {}: request dropped", id),
+                _ = fetch => {}
+            }
+        });
+    }
+
+    /// Why a direct request to `url` must not go out, per `target_violation`. Proxied names are
+    /// resolved by the proxy, so only direct requests are checked; the direct clients' resolver
+    /// takes care of names that lead to the local network.
+    fn blocked_target(&self, url: &str) -> Option<String> {
+        let use_i2p = self.i2p_mode;
+        Url::parse(url)
+            .ok()
+            .filter(|url| {
+                !use_i2p
+                    && !self.config.allow_local_targets
+                    && !url
+                        .host_str()
+                        .is_some_and(|host| host.ends_with(".i2p") || host.ends_with(".onion"))
+            })
+            .and_then(|url| target_violation(&url))
+    }
+
+    /// Show a built-in `about:` page; it arrives like a fetched one but never touches the network
+    fn submit_internal_page(&mut self, url: String) {
+        let tab = &self.tabs[self.active_tab_index];
+        let (title, html) = about::page(&url, &tab.history, &self.downloads, &self.keymap);
+        let id = tab.id;
+        log::info!("tab {}: internal page {}", id, url);
+        // Supersedes anything still loading in the tab
+        self.current_tab().begin_load();
+        let info = PageInfo {
+            final_url: url.clone(),
+            status: 200,
+            content_type: Some(String::from("text/html")),
+            ..Default::default()
+        };
+        let tx_clone = self.tx.clone();
+        tokio::spawn(async move {
+            let _ = tx_clone.send(NetworkResponse::ResponseInfo(id, info)).await;
+            let _ = tx_clone
+                .send(NetworkResponse::Success(id, url, title, html, Duration::ZERO))
+                .await;
+        });
+    }
+
+    /// Fetch a `gemini://` page, following redirects, and hand the gemtext over as HTML
+    fn submit_gemini_request(&mut self, target_url: String) {
+        let id = self.current_tab().id;
+        log::info!("tab {}: GEMINI {}", id, target_url);
+        let tx_clone = self.tx.clone();
+        if self.i2p_mode {
+            tokio::spawn(async move {
+                let _ = tx_clone
+                    .send(NetworkResponse::Error(
+                        id,
+                        String::from("Gemini isn't available in I2P mode"),
+                    ))
+                    .await;
+            });
+            return;
+        }
+        let known_hosts = Arc::clone(&self.known_hosts);
+        let max_page_size = self.config.max_page_size;
+        let allow_local_targets = self.config.allow_local_targets;
+        let blocked = self.blocked_target(&target_url);
+        let cancel = self.current_tab().begin_load();
+
+        tokio::spawn(async move {
+            let _ = tx_clone.send(NetworkResponse::Loading(id)).await;
+            if let Some(reason) = blocked {
+                log::warn!("tab {}: {}", id, reason);
+                let _ = tx_clone.send(NetworkResponse::Error(id, reason)).await;
+                return;
             }
+            let started = Instant::now();
+            // Phases are only status text, so one that doesn't fit in the channel is skipped
+            let phase_tx = tx_clone.clone();
+            let on_phase = move |stage: &str, host: &str| {
+                let message = format!("{} {}...", stage, host);
+                let _ = phase_tx.try_send(NetworkResponse::Info(id, message));
+            };
+            let result = tokio::select! {
+                biased;
+                _ = cancel.cancelled() => return,
+                result = gemini::fetch_page(
+                    &target_url,
+                    known_hosts,
+                    max_page_size,
+                    allow_local_targets,
+                    &on_phase,
+                ) => result,
+            };
+            let response = match result {
+                Ok((info, title, html)) => {
+                    let final_url = info.final_url.clone();
+                    let _ = tx_clone.send(NetworkResponse::ResponseInfo(id, info)).await;
+                    NetworkResponse::Success(id, final_url, title, html, started.elapsed())
+                }
+                Err(msg) => NetworkResponse::Error(id, msg),
+            };
+            let _ = tx_clone.send(response).await;
         });
     }
 
@@ -397,76 +1910,310 @@ impl App {
         }
     }
     pub fn trigger_download(&mut self, url: String) {
+        self.trigger_download_with_checksum(url, None);
+    }
+
+    /// Switch every client to a new user agent. Requests already in flight finish with the old one;
+    /// the per-host request spacing carries over.
+    pub fn set_user_agent(&mut self, name_or_agent: &str) {
+        let agent = resolve_user_agent(name_or_agent);
+        match NetworkManager::with_settings(
+            &agent,
+            self.config.allow_https_downgrade,
+            self.config.allow_local_targets,
+            Arc::clone(&self.throttle),
+        ) {
+            Ok(manager) => {
+                self.network_manager = Arc::new(manager);
+                let label = user_agent_preset_name(&agent).unwrap_or(&agent).to_string();
+                self.current_tab().set_transient_status(format!("User agent: {}", label));
+            }
+            Err(e) => {
+                self.current_tab().set_transient_status(format!("Invalid user agent: {}", e));
+            }
+        }
+    }
+
+    /// Move to the next user agent preset; a custom UA goes back to the first preset
+    pub fn cycle_user_agent(&mut self) {
+        let current = self.network_manager.user_agent();
+        let next = USER_AGENT_PRESETS
+            .iter()
+            .position(|(_, agent)| *agent == current)
+            .map(|index| (index + 1) % USER_AGENT_PRESETS.len())
+            .unwrap_or(0);
+        self.set_user_agent(USER_AGENT_PRESETS[next].0);
+    }
+
+    /// Handle `:ua <preset|string>` typed into the URL bar.
+    /// Returns false when the input isn't a user agent command.
+    pub fn run_user_agent_command(&mut self, input: &str) -> bool {
+        let Some(args) = input.trim().strip_prefix(":ua") else {
+            return false;
+        };
+        let args = args.trim();
+        if args.is_empty() {
+            let names: Vec<&str> = USER_AGENT_PRESETS.iter().map(|(name, _)| *name).collect();
+            self.current_tab()
+                .set_transient_status(format!("Usage: :ua <{}|string>", names.join("|")));
+            return true;
+        }
+        self.set_user_agent(args);
+        true
+    }
+
+    /// Handle `:pipe <command>` typed into the URL bar by feeding the page text to its stdin.
+    /// Returns false when the input isn't a pipe command.
+    pub fn run_pipe_command(&mut self, input: &str) -> bool {
+        let Some(command_line) = input.trim().strip_prefix(":pipe") else {
+            return false;
+        };
+        let command_line = command_line.trim().to_string();
+        if command_line.is_empty() {
+            self.current_tab().set_transient_status("Usage: :pipe <command>");
+            return true;
+        }
+        let text = self.current_tab().page_text();
+        let result = (self.pipe_runner)(shell_command(&command_line), text);
+        self.needs_full_redraw = true;
+        let message = match result {
+            Ok(()) => format!("Piped page to {}", command_line),
+            Err(e) => format!("{} failed: {}", command_line, e),
+        };
+        self.current_tab().set_transient_status(message);
+        true
+    }
+
+    /// Handle `:download <url> [sha256=<hex>]` typed into the URL bar.
+    /// Returns false when the input isn't a download command.
+    pub fn run_download_command(&mut self, input: &str) -> bool {
+        let Some(args) = input.trim().strip_prefix(":download") else {
+            return false;
+        };
+        let mut url = None;
+        let mut checksum = None;
+        for arg in args.split_whitespace() {
+            match arg.strip_prefix("sha256=") {
+                Some(hex) => checksum = Some(hex.to_ascii_lowercase()),
+                None => url = Some(arg.to_string()),
+            }
+        }
+
+        let Some(url) = url else {
+            self.current_tab().set_transient_status("Usage: :download <url> [sha256=<hash>]");
+            return true;
+        };
+        if let Some(hex) = &checksum
+            && (hex.len() != 64 || !hex.chars().all(|c| c.is_ascii_hexdigit()))
+        {
+            self.current_tab().set_transient_status("SHA-256 must be 64 hex characters");
+            return true;
+        }
+        self.trigger_download_with_checksum(url, checksum);
+        true
+    }
+
+    pub fn trigger_download_with_checksum(&mut self, url: String, expected_sha256: Option<String>) {
         let tab_id = self.current_tab().id;
         let tx = self.tx.clone();
         let use_i2p = self.i2p_mode;
         let nm = Arc::clone(&self.network_manager);
 
+        // Register the download up front so the manager lists it before the first chunk
+        let download_id = self.next_download_id;
+        self.next_download_id += 1;
+        let now = Instant::now();
+        let entry = Download {
+            id: download_id,
+            tab_id,
+            url: url.clone(),
+            filename: Self::sanitize_filename(url.split('/').next_back().unwrap_or("download.dat")),
+            bytes_downloaded: 0,
+            total_size: None,
+            status: DownloadStatus::Active,
+            saved_path: None,
+            started: now,
+            last_update: now,
+            cancel_token: CancellationToken::new(),
+            expected_sha256: expected_sha256.clone(),
+        };
+        let token = entry.cancel_token.clone();
+        let blocked = self.blocked_target(&url);
+        let keep_partial = self.config.keep_partial_downloads;
+        let conflict_policy = self.config.download_conflict;
+        let download_dir = self.config.download_dir();
+        self.current_tab().download_state = Some(entry.clone());
+        self.downloads.insert(download_id, entry);
+
         tokio::spawn(async move {
+            if let Some(reason) = blocked {
+                log::warn!("tab {}: {}", tab_id, reason);
+                let _ = tx
+                    .send(NetworkResponse::DownloadFailed(tab_id, download_id, reason))
+                    .await;
+                return;
+            }
             let client = nm.get_download_client(use_i2p);
 
             // 1. Send request
-            let res = match client.get(&url).send().await {
+            let request = tokio::select! {
+                biased;
+                _ = token.cancelled() => return,
+                r = client.get(&url).send() => r,
+            };
+            let res = match request {
                 Ok(r) => r,
                 Err(e) => {
-                    let _ = tx.send(NetworkResponse::Error(tab_id, format!("Connection failed: {}", e))).await;
+                    let _ = tx.send(NetworkResponse::DownloadFailed(tab_id, download_id, format!("Connection failed: {}", e))).await;
                     return;
                 }
             };
 
             let total = res.content_length();
+            let disposition_name = res
+                .headers()
+                .get(reqwest::header::CONTENT_DISPOSITION)
+                .and_then(|v| v.to_str().ok())
+                .and_then(crate::network::content_disposition_filename);
             let mut stream = res.bytes_stream();
             let mut downloaded: u64 = 0;
             let mut file_handle: Option<(tokio::fs::File, String)> = None;
+            // Hash while streaming so verification needs no second pass over the file
+            let mut hasher = expected_sha256
+                .as_ref()
+                .map(|_| digest::Context::new(&digest::SHA256));
+            // Set when the body breaks off or can't be written, so the file isn't passed off as saved
+            let mut failure: Option<String> = None;
 
-            while let Some(item) = stream.next().await {
-                if let Ok(chunk) = item {
-                    if file_handle.is_none() {
-                        // 2. Resolve and Create Downloads Directory
-                        let download_dir = UserDirs::new()
-                            .and_then(|d| d.download_dir().map(|p| p.to_path_buf()))
-                            .unwrap_or_default();
-
-                        if let Err(e) = tokio::fs::create_dir_all(&download_dir).await {
-                            let _ = tx.send(NetworkResponse::Error(tab_id, format!("Folder error: {}", e))).await;
-                            return;
+            loop {
+                let item = tokio::select! {
+                    biased;
+                    _ = token.cancelled() => {
+                        if let Some((file, path)) = file_handle.take() {
+                            drop(file);
+                            if !keep_partial {
+                                let _ = tokio::fs::remove_file(&path).await;
+                            }
                         }
+                        return;
+                    }
+                    item = stream.next() => item,
+                };
+                let Some(item) = item else { break };
+                let chunk = match item {
+                    Ok(chunk) => chunk,
+                    Err(e) => {
+                        failure = Some(format!("Download interrupted: {}", e));
+                        break;
+                    }
+                };
+                if file_handle.is_none() {
+                    // 2. Create the Downloads Directory if needed
 
-                        // 3. Sanitize and Sniff Extension
-                        let raw_name = url.split('/').last().unwrap_or("download.dat");
-                        let mut sanitized = Self::sanitize_filename(raw_name);
+                    if let Err(e) = tokio::fs::create_dir_all(&download_dir).await {
+                        let _ = tx.send(NetworkResponse::DownloadFailed(tab_id, download_id, format!("Folder error: {}", e))).await;
+                        return;
+                    }
 
-                        if !sanitized.contains('.') {
-                            if let Some(ext) = crate::network::sniff_extension(&chunk) {
-                                sanitized.push_str(&format!(".{}", ext));
-                            }
+                    // 3. Sanitize and Sniff Extension
+                    let raw_name = disposition_name
+                        .as_deref()
+                        .unwrap_or_else(|| url.split('/').next_back().unwrap_or("download.dat"));
+                    let mut sanitized = Self::sanitize_filename(raw_name);
+
+                    if !sanitized.contains('.') {
+                        if let Some(ext) = crate::network::sniff_extension(&chunk) {
+                            sanitized.push_str(&format!(".{}", ext));
                         }
+                    }
 
-                        let file_path = download_dir.join(&sanitized);
+                    let mut file_path = download_dir.join(&sanitized);
 
-                        // 4. Safe File Creation (No unwraps)
-                        let file = match tokio::fs::File::create(&file_path).await {
-                            Ok(f) => f,
-                            Err(e) => {
-                                let _ = tx.send(NetworkResponse::Error(tab_id, format!("File error: {}", e))).await;
-                                return;
+                    // Never clobber an existing file without the user's say-so
+                    if file_path.exists() {
+                        let choice = match conflict_policy {
+                            DownloadConflictPolicy::Rename => ConflictChoice::Rename,
+                            DownloadConflictPolicy::Prompt => {
+                                let (reply_tx, reply_rx) = oneshot::channel();
+                                let path = file_path.display().to_string();
+                                let _ = tx.send(NetworkResponse::DownloadConflict(tab_id, download_id, path, reply_tx)).await;
+                                tokio::select! {
+                                    biased;
+                                    _ = token.cancelled() => return,
+                                    choice = reply_rx => choice.unwrap_or(ConflictChoice::Cancel),
+                                }
                             }
                         };
-                        file_handle = Some((file, file_path.display().to_string()));
+                        match choice {
+                            ConflictChoice::Overwrite => {}
+                            ConflictChoice::Rename => file_path = unique_download_path(&file_path),
+                            ConflictChoice::Cancel => return,
+                        }
                     }
 
-                    if let Some((ref mut file, _)) = file_handle {
-                        if file.write_all(&chunk).await.is_err() { break; }
-                        downloaded += chunk.len() as u64;
-                        let _ = tx.send(NetworkResponse::DownloadProgress(tab_id, downloaded, total)).await;
+                    // 4. Safe File Creation (No unwraps)
+                    let file = match tokio::fs::File::create(&file_path).await {
+                        Ok(f) => f,
+                        Err(e) => {
+                            let _ = tx.send(NetworkResponse::DownloadFailed(tab_id, download_id, format!("File error: {}", e))).await;
+                            return;
+                        }
+                    };
+                    file_handle = Some((file, file_path.display().to_string()));
+                }
+
+                if let Some((ref mut file, _)) = file_handle {
+                    if let Err(e) = file.write_all(&chunk).await {
+                        failure = Some(format!("File error: {}", e));
+                        break;
+                    }
+                    if let Some(h) = hasher.as_mut() {
+                        h.update(&chunk);
                     }
+                    downloaded += chunk.len() as u64;
+                    let _ = tx.send(NetworkResponse::DownloadProgress(tab_id, download_id, downloaded, total)).await;
                 }
             }
 
-            if let Some((_, path)) = file_handle {
-                let _ = tx.send(NetworkResponse::DownloadFinished(tab_id, path)).await;
+            if token.is_cancelled() {
+                return;
+            }
+            // A body that ended short of its Content-Length was cut off, not finished
+            if failure.is_none()
+                && file_handle.is_some()
+                && let Some(total) = total.filter(|&total| total != downloaded)
+            {
+                failure = Some(format!("Download incomplete ({} of {} bytes)", downloaded, total));
+            }
+            if let Some(msg) = failure {
+                if let Some((file, path)) = file_handle.take() {
+                    drop(file);
+                    if !keep_partial {
+                        let _ = tokio::fs::remove_file(&path).await;
+                    }
+                }
+                let _ = tx.send(NetworkResponse::DownloadFailed(tab_id, download_id, msg)).await;
+                return;
+            }
+            if let Some((mut file, path)) = file_handle {
+                // Make sure the bytes are on disk before announcing the file
+                if let Err(e) = file.flush().await {
+                    let _ = tx.send(NetworkResponse::DownloadFailed(tab_id, download_id, format!("File error: {}", e))).await;
+                    return;
+                }
+                if let (Some(expected), Some(h)) = (&expected_sha256, hasher) {
+                    let actual: String = h.finish().as_ref().iter().map(|b| format!("{:02x}", b)).collect();
+                    if &actual != expected {
+                        drop(file);
+                        let _ = tokio::fs::remove_file(&path).await;
+                        let msg = format!("Checksum mismatch (got {})", actual);
+                        let _ = tx.send(NetworkResponse::DownloadFailed(tab_id, download_id, msg)).await;
+                        return;
+                    }
+                }
+                let _ = tx.send(NetworkResponse::DownloadFinished(tab_id, download_id, path)).await;
             } else {
-                let _ = tx.send(NetworkResponse::Error(tab_id, "Download stream was empty".to_string())).await;
+                let _ = tx.send(NetworkResponse::DownloadFailed(tab_id, download_id, "Download stream was empty".to_string())).await;
             }
         });
     }
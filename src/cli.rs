@@ -0,0 +1,84 @@
+use crate::constants::DEFAULT_DUMP_WIDTH;
+
+pub const USAGE: &str = "\
+Usage: rynx [OPTIONS] [URL]...
+       rynx --dump <URL> [--width <COLUMNS>]
+
+Opens each URL in its own tab, the first in front; with none, starts on the home page.
+With --dump, prints the page at URL as plain text and exits instead.
+
+Options:
+  -u, --url <URL>        Open URL, like a positional argument
+      --dump <URL>       Print the page at URL as plain text and exit, without the interface
+      --width <COLUMNS>  Wrap dumped text at this many columns (default 80)
+  -h, --help             Show this help and exit";
+
+/// What the command line asked for
+#[derive(Debug, PartialEq)]
+pub enum CliAction {
+    /// Start the browser with these pages open, in order
+    Run(Vec<String>),
+    /// Fetch one page, print it as text `width` columns wide and exit
+    Dump { url: String, width: u16 },
+    /// Print the usage and exit
+    Help,
+}
+
+/// An option's value, from the next argument or after `=`; it can't be empty
+fn option_value(option: &str, value: Option<String>, what: &str) -> Result<String, String> {
+    match value {
+        Some(value) if !value.is_empty() => Ok(value),
+        _ => Err(format!("{} needs {}", option, what)),
+    }
+}
+
+fn parse_width(option: &str, value: Option<String>) -> Result<u16, String> {
+    let value = option_value(option, value, "a number of columns")?;
+    value
+        .parse()
+        .ok()
+        .filter(|&width| width > 0)
+        .ok_or_else(|| format!("{} needs a number of columns, got '{}'", option, value))
+}
+
+/// Parse the arguments after the program name. Errors say what was wrong, for printing
+/// above the usage.
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<CliAction, String> {
+    let mut urls = Vec::new();
+    let mut dump = None;
+    let mut width = None;
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(CliAction::Help),
+            "-u" | "--url" => urls.push(option_value(&arg, args.next(), "a URL")?),
+            "--dump" => dump = Some(option_value(&arg, args.next(), "a URL")?),
+            "--width" => width = Some(parse_width(&arg, args.next())?),
+            // Everything after a bare `--` is a URL, even if it starts with a dash
+            "--" => urls.extend(args.by_ref()),
+            _ => match arg.split_once('=') {
+                Some(("--url", url)) => {
+                    urls.push(option_value("--url", Some(url.to_string()), "a URL")?)
+                }
+                Some(("--dump", url)) => {
+                    dump = Some(option_value("--dump", Some(url.to_string()), "a URL")?)
+                }
+                Some(("--width", columns)) => {
+                    width = Some(parse_width("--width", Some(columns.to_string()))?)
+                }
+                _ if arg.starts_with('-') => return Err(format!("unknown option '{}'", arg)),
+                _ if arg.trim().is_empty() => return Err(String::from("empty URL")),
+                _ => urls.push(arg),
+            },
+        }
+    }
+    match dump {
+        Some(_) if !urls.is_empty() => Err(String::from("--dump prints a single page")),
+        Some(url) => Ok(CliAction::Dump {
+            url,
+            width: width.unwrap_or(DEFAULT_DUMP_WIDTH),
+        }),
+        None if width.is_some() => Err(String::from("--width only applies to --dump")),
+        None => Ok(CliAction::Run(urls)),
+    }
+}
@@ -0,0 +1,205 @@
+use crate::constants::{
+    CONFIG_FILE_NAME, CONTROL_SOCKET_ENV, DEFAULT_TAB_STOP, EVENT_POLL_TIMEOUT_MS,
+    GEMINI_KNOWN_HOSTS_FILE, JUMP_SERVICES, JUMP_TIMEOUT_MS, KEYMAP_FILE_NAME, LOG_FILE_ENV,
+    MAX_HISTORY_ENTRIES, MAX_LINK_REGIONS, MAX_PAGE_SIZE_BYTES, MAX_TABS, REQUEST_INTERVAL_MS,
+    SESSION_FILE_NAME, TRACKING_PARAMS,
+};
+use directories::{ProjectDirs, UserDirs};
+use serde::Deserialize;
+use std::path::PathBuf;
+
+/// User settings loaded from `config.json` in the platform config directory.
+/// Every field is optional in the file; missing ones fall back to the defaults below.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Remove tracking query parameters (utm_*, fbclid, ...) before navigating
+    pub strip_tracking_params: bool,
+    /// Parameter names to strip; a trailing `*` matches any suffix
+    pub tracking_params: Vec<String>,
+    /// Leave the partially written file on disk when a download is cancelled or breaks off
+    pub keep_partial_downloads: bool,
+    /// What to do when a download would replace an existing file
+    pub download_conflict: DownloadConflictPolicy,
+    /// Where downloads are saved; the OS Downloads folder when unset
+    pub download_dir: Option<PathBuf>,
+    /// Send a HEAD request for ambiguous links to decide between opening and downloading
+    pub head_check_links: bool,
+    /// Most links registered as navigable on a single page
+    pub max_link_regions: usize,
+    /// Back stack entries kept per tab
+    pub max_history: usize,
+    /// Most tabs open at once; opening more is refused
+    pub max_tabs: usize,
+    /// Least time between page requests to the same host, in milliseconds; 0 turns it off
+    pub request_interval_ms: u64,
+    /// Largest page body in bytes; bigger pages are abandoned mid-transfer
+    pub max_page_size: u64,
+    /// User agent preset name (default, firefox, chrome, curl) or a full UA string
+    pub user_agent: Option<String>,
+    /// Reuse HTTP Basic credentials for the same host until the browser exits
+    pub remember_credentials: bool,
+    /// File pinning Gemini server certificates; the app data directory when unset
+    pub gemini_known_hosts: Option<PathBuf>,
+    /// When started without URLs, reopen the tabs saved by the last such run, with their column
+    /// widths, and save them again on quit. Off by default, since the saved addresses may carry
+    /// tokens in their queries
+    pub restore_session: bool,
+    /// Where open tabs are saved on quit; the app data directory when unset
+    pub session_file: Option<PathBuf>,
+    /// Where a link or search match that Tab or `>`/`<` moved off-screen is scrolled to
+    pub autoscroll: AutoscrollMode,
+    /// Spinners and other moving indicators; off shows static text instead
+    pub animations: bool,
+    /// How long the idle main loop waits for input before checking on other work, in
+    /// milliseconds. Longer saves CPU when idle; loading pages and downloads aren't slowed.
+    pub poll_interval_ms: u64,
+    /// Follow page redirects from HTTPS to plain HTTP instead of refusing them
+    pub allow_https_downgrade: bool,
+    /// Offer to load a page whose certificate was refused anyway, skipping the check for that
+    /// host until the browser exits
+    pub allow_insecure_retry: bool,
+    /// Columns per tab stop when expanding tabs in preformatted text
+    pub tab_width: usize,
+    /// Prefix page headings with their section number (1, 1.1, 1.2, 2, ...)
+    pub number_headings: bool,
+    /// Send the origin of the page a link was followed from as the Referer, unless the link is
+    /// `rel="noreferrer"`
+    pub send_referer: bool,
+    /// Let typed URLs and links reach localhost and private network addresses
+    pub allow_local_targets: bool,
+    /// Jump service URLs asked, in order, for I2P addresses the router doesn't know
+    pub jump_services: Vec<String>,
+    /// How long each jump service has to answer before the next is asked, in milliseconds
+    pub jump_timeout_ms: u64,
+    /// Key bindings file; `keymap.json` next to the config file when unset
+    pub keymap_file: Option<PathBuf>,
+    /// Append a debug log of navigations and responses here; logging is off when unset
+    pub log_file: Option<PathBuf>,
+    /// Accept `open`, `next-tab`, `scroll` and `dump-text` commands on a unix socket at this
+    /// path; off when unset
+    pub control_socket: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum DownloadConflictPolicy {
+    /// Ask whether to overwrite, rename or cancel
+    #[default]
+    Prompt,
+    /// Save alongside the existing file with a ` (1)` style suffix
+    Rename,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AutoscrollMode {
+    /// Scroll just far enough to show it at the top or bottom edge
+    #[default]
+    Edge,
+    /// Scroll so it sits in the middle of the viewport
+    Center,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            strip_tracking_params: true,
+            tracking_params: TRACKING_PARAMS.iter().map(|p| p.to_string()).collect(),
+            keep_partial_downloads: false,
+            download_conflict: DownloadConflictPolicy::Prompt,
+            download_dir: None,
+            head_check_links: false,
+            max_link_regions: MAX_LINK_REGIONS,
+            max_history: MAX_HISTORY_ENTRIES,
+            max_tabs: MAX_TABS,
+            max_page_size: MAX_PAGE_SIZE_BYTES,
+            request_interval_ms: REQUEST_INTERVAL_MS,
+            user_agent: None,
+            remember_credentials: true,
+            gemini_known_hosts: None,
+            restore_session: false,
+            session_file: None,
+            autoscroll: AutoscrollMode::Edge,
+            animations: true,
+            poll_interval_ms: EVENT_POLL_TIMEOUT_MS,
+            allow_https_downgrade: false,
+            allow_insecure_retry: false,
+            tab_width: DEFAULT_TAB_STOP,
+            number_headings: false,
+            send_referer: false,
+            allow_local_targets: false,
+            jump_services: JUMP_SERVICES.iter().map(|s| s.to_string()).collect(),
+            jump_timeout_ms: JUMP_TIMEOUT_MS,
+            keymap_file: None,
+            log_file: None,
+            control_socket: None,
+        }
+    }
+}
+
+impl Config {
+    /// Location of the config file, if the platform has a config directory
+    pub fn path() -> Option<PathBuf> {
+        ProjectDirs::from("", "", "rynx").map(|dirs| dirs.config_dir().join(CONFIG_FILE_NAME))
+    }
+
+    /// Load the user's config, falling back to defaults when it is missing or invalid
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| std::fs::read_to_string(path).ok())
+            .and_then(|text| Self::from_json(&text).ok())
+            .unwrap_or_default()
+    }
+
+    /// Resolved download directory; it may not exist yet
+    pub fn download_dir(&self) -> PathBuf {
+        self.download_dir
+            .clone()
+            .or_else(|| UserDirs::new().and_then(|d| d.download_dir().map(|p| p.to_path_buf())))
+            .unwrap_or_default()
+    }
+
+    /// Resolved Gemini known hosts file, if the platform has a data directory
+    pub fn gemini_known_hosts(&self) -> Option<PathBuf> {
+        self.gemini_known_hosts.clone().or_else(|| {
+            ProjectDirs::from("", "", "rynx")
+                .map(|dirs| dirs.data_dir().join(GEMINI_KNOWN_HOSTS_FILE))
+        })
+    }
+
+    /// Resolved session file, if the platform has a data directory
+    pub fn session_file(&self) -> Option<PathBuf> {
+        self.session_file.clone().or_else(|| {
+            ProjectDirs::from("", "", "rynx").map(|dirs| dirs.data_dir().join(SESSION_FILE_NAME))
+        })
+    }
+
+    /// Resolved key bindings file, if the platform has a config directory
+    pub fn keymap_file(&self) -> Option<PathBuf> {
+        self.keymap_file.clone().or_else(|| {
+            ProjectDirs::from("", "", "rynx").map(|dirs| dirs.config_dir().join(KEYMAP_FILE_NAME))
+        })
+    }
+
+    /// Log file to write, from the `RYNX_LOG` environment variable or else the config
+    pub fn log_file(&self) -> Option<PathBuf> {
+        std::env::var_os(LOG_FILE_ENV)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| self.log_file.clone())
+    }
+
+    /// Control socket to listen on, from the `RYNX_CONTROL_SOCKET` environment variable or
+    /// else the config
+    pub fn control_socket(&self) -> Option<PathBuf> {
+        std::env::var_os(CONTROL_SOCKET_ENV)
+            .filter(|value| !value.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| self.control_socket.clone())
+    }
+
+    pub fn from_json(text: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(text)
+    }
+}
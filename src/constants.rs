@@ -1,10 +1,25 @@
 // User Agent constant
 pub const USER_AGENT: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/26.0 Safari/605.1.15";
 
+// Named user agents for the `user_agent` setting, `:ua` and `U`; cycled in this order
+pub const USER_AGENT_PRESETS: &[(&str, &str)] = &[
+    ("default", USER_AGENT),
+    (
+        "firefox",
+        "Mozilla/5.0 (X11; Linux x86_64; rv:143.0) Gecko/20100101 Firefox/143.0",
+    ),
+    (
+        "chrome",
+        "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/141.0.0.0 Safari/537.36",
+    ),
+    ("curl", "curl/8.16.0"),
+];
+
 // Network configuration
 pub const I2P_PROXY_URL: &str = "http://127.0.0.1:4444";
 pub const BROWSING_TIMEOUT_SECS: u64 = 100;
 pub const DOWNLOAD_TIMEOUT_SECS: u64 = 3000;
+pub const REQUEST_INTERVAL_MS: u64 = 250; // Default spacing of page requests to one host
 
 // Channel capacity
 pub const CHANNEL_CAPACITY: usize = 10;
@@ -16,9 +31,18 @@ pub const UI_ROW_OFFSET: u16 = 7;
 pub const UI_HEIGHT_OFFSET: u16 = 8;
 pub const UI_BORDER_WIDTH: usize = 2;
 pub const MOUSE_SCROLL_LINES: usize = 3;
+pub const HORIZONTAL_SCROLL_COLUMNS: usize = 8; // Columns moved per H/L press
+pub const COLUMN_WIDTH_STEP: usize = 10; // Columns a tab's text narrows or widens per -/+ press
+pub const MIN_COLUMN_WIDTH: usize = 20; // Narrowest a tab's text column can be set
+pub const MAX_MOTION_COUNT: usize = 9999; // Upper bound for numeric count prefixes (e.g. 5j)
+pub const DEFAULT_TAB_STOP: usize = 4; // Columns per tab stop in preformatted text
+pub const FORM_FIELD_WIDTH: usize = 20; // Columns inside the brackets of a rendered text input
+pub const TABLE_COLUMN_GAP: usize = 2; // Spaces between the columns of a rendered table
+pub const CODE_WRAP_MARKER: &str = "\u{21aa} "; // Starts the continuation rows of a wrapped code line
+pub const MIN_TERMINAL_HEIGHT: u16 = TAB_BAR_HEIGHT + URL_BAR_HEIGHT + 3; // Chrome plus one bordered content row
 
 // File size limits
-pub const MAX_PAGE_SIZE_BYTES: u64 = 10 * 1024 * 1024; // 10MB
+pub const MAX_PAGE_SIZE_BYTES: u64 = 10 * 1024 * 1024; // 10MB, default for `max_page_size`
 
 // Tab navigation
 pub const DEFAULT_TAB_INDEX: usize = 0;
@@ -31,13 +55,43 @@ pub const JUMP_SERVICES: &[&str] = &[
     "http://stats.i2p/jump/",
     "http://reg.i2p/jump/",
 ];
+pub const JUMP_TIMEOUT_MS: u64 = 15_000; // Per jump service, before the next one is asked
 
 // Event polling
 pub const EVENT_POLL_TIMEOUT_MS: u64 = 10;
+pub const STATUS_MESSAGE_TIMEOUT_MS: u64 = 3000; // Transient status messages revert to "Ready" after this
+pub const RESIZE_DEBOUNCE_MS: u64 = 50; // Quiet period before reflowing after a resize burst
+
+// Loading spinner, shown unless animations are turned off in the config
+pub const SPINNER_FRAMES: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+pub const SPINNER_FRAME_MS: u128 = 100;
 
 // Redirect policy
 pub const MAX_REDIRECTS: usize = 10;
 
+// Gemini protocol
+pub const GEMINI_DEFAULT_PORT: u16 = 1965;
+pub const GEMINI_KNOWN_HOSTS_FILE: &str = "gemini_known_hosts";
+
+// Open tabs saved on quit, next to the Gemini known hosts unless `session_file` says otherwise
+pub const SESSION_FILE_NAME: &str = "session.json";
+
+// Config file
+pub const CONFIG_FILE_NAME: &str = "config.json";
+pub const KEYMAP_FILE_NAME: &str = "keymap.json"; // Next to config.json unless `keymap_file` says otherwise
+
+// Debug log; the environment variable names a log file and overrides `log_file` in the config
+pub const LOG_FILE_ENV: &str = "RYNX_LOG";
+
+// Control socket for scripts; the environment variable names its path and overrides the config
+pub const CONTROL_SOCKET_ENV: &str = "RYNX_CONTROL_SOCKET";
+
+// Query parameters removed before navigating (a trailing * matches any suffix)
+pub const TRACKING_PARAMS: &[&str] = &[
+    "utm_*", "fbclid", "gclid", "dclid", "gbraid", "wbraid", "msclkid", "mc_cid", "mc_eid",
+    "igshid", "yclid", "_hsenc", "_hsmkt",
+];
+
 // Search URLs
 pub const MARGINALIA_SEARCH_URL: &str = "https://search.marginalia.nu/search?";
 
@@ -47,3 +101,15 @@ pub const DOWNLOAD_PROMPT_Y_DIVISOR: u16 = 2;     // Vertical Center
 pub const DOWNLOAD_PROMPT_HEIGHT: u16 = 9;
 pub const DOWNLOAD_PROMPT_Y_OFFSET: u16 = 4;      // Vertical centering offset, height / 2
 pub const DOWNLOAD_PROMPT_BUTTON_ROW_OFFSET: u16 = 5; // Row inside the prompt for Y/N
+
+// Upper bound on clickable link regions per page, keeps Tab cycling and hit-testing fast
+pub const MAX_LINK_REGIONS: usize = 50_000;
+
+// Open tabs allowed at once; each can own running network tasks
+pub const MAX_TABS: usize = 50;
+
+// Columns `--dump` wraps page text at unless `--width` says otherwise
+pub const DEFAULT_DUMP_WIDTH: u16 = 80;
+
+// Back stack entries kept per tab before the oldest are dropped
+pub const MAX_HISTORY_ENTRIES: usize = 500;
@@ -0,0 +1,187 @@
+use crate::app::App;
+use crate::constants::CHANNEL_CAPACITY;
+use tokio::sync::{mpsc, oneshot};
+
+/// A line sent to the control socket, mapped onto what the keyboard would do
+#[derive(Clone, Debug, PartialEq)]
+pub enum ControlCommand {
+    /// `open <url>`: navigate the active tab, as if following a link
+    Open(String),
+    /// `next-tab`: switch to the next tab, wrapping around
+    NextTab,
+    /// `scroll <n>`: scroll the active tab by `n` lines; negative scrolls up
+    Scroll(isize),
+    /// `dump-text`: the active tab's rendered page as plain text
+    DumpText,
+}
+
+impl ControlCommand {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+        let (name, argument) = match line.split_once(char::is_whitespace) {
+            Some((name, rest)) => (name, rest.trim()),
+            None => (line, ""),
+        };
+        match (name, argument) {
+            ("open", "") => Err(String::from("open needs a URL")),
+            ("open", url) => Ok(Self::Open(url.to_string())),
+            ("next-tab", "") => Ok(Self::NextTab),
+            ("scroll", lines) => lines
+                .parse()
+                .map(Self::Scroll)
+                .map_err(|_| format!("scroll needs a line count, got '{}'", lines)),
+            ("dump-text", "") => Ok(Self::DumpText),
+            ("next-tab" | "dump-text", _) => Err(format!("{} takes no arguments", name)),
+            _ => Err(format!("unknown command '{}'", name)),
+        }
+    }
+}
+
+/// A parsed command waiting for the main loop, and where to send its reply
+pub struct ControlRequest {
+    pub command: ControlCommand,
+    pub reply: oneshot::Sender<String>,
+}
+
+/// Reply framing: `ok <n>` followed by `n` lines of output, or a single `error <message>` line
+fn reply_ok(output: &str) -> String {
+    if output.is_empty() {
+        return String::from("ok 0\n");
+    }
+    format!("ok {}\n{}\n", output.split('\n').count(), output)
+}
+
+fn reply_error(message: &str) -> String {
+    format!("error {}\n", message)
+}
+
+/// Carry out one command against the app and return the reply to send back
+pub fn execute(app: &mut App, command: ControlCommand, terminal_height: u16) -> String {
+    app.dirty = true;
+    match command {
+        ControlCommand::Open(url) => {
+            app.open_link(url, false);
+            reply_ok("")
+        }
+        ControlCommand::NextTab => {
+            let next = (app.active_tab_index + 1) % app.tabs.len();
+            app.select_tab(next, terminal_height);
+            reply_ok("")
+        }
+        ControlCommand::Scroll(lines) => {
+            let viewport_height = app.viewport_height(terminal_height);
+            let tab = app.current_tab();
+            tab.scroll = tab.scroll.saturating_add_signed(lines);
+            tab.clamp_view(viewport_height);
+            reply_ok("")
+        }
+        ControlCommand::DumpText => reply_ok(&app.current_tab().page_text()),
+    }
+}
+
+/// Run every command that has arrived since the last call; never waits for more.
+/// Returns false once the listener has gone away.
+pub fn handle_requests(
+    app: &mut App,
+    requests: &mut mpsc::Receiver<ControlRequest>,
+    terminal_height: u16,
+) -> bool {
+    loop {
+        match requests.try_recv() {
+            Ok(request) => {
+                let reply = execute(app, request.command, terminal_height);
+                // The client may have hung up while waiting
+                let _ = request.reply.send(reply);
+            }
+            Err(mpsc::error::TryRecvError::Empty) => return true,
+            Err(mpsc::error::TryRecvError::Disconnected) => return false,
+        }
+    }
+}
+
+/// Listen on a unix socket at `path`, readable and writable only by the current user.
+/// Each connection sends one command per line and gets a reply per command; the commands
+/// themselves come out of the returned channel for the main loop to run between frames.
+#[cfg(unix)]
+pub fn listen(path: &std::path::Path) -> std::io::Result<mpsc::Receiver<ControlRequest>> {
+    use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+    use tokio::net::UnixListener;
+
+    // A socket left behind by a browser that didn't exit cleanly is replaced; anything else isn't
+    if let Ok(metadata) = std::fs::symlink_metadata(path) {
+        if !metadata.file_type().is_socket() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("{} exists and isn't a socket", path.display()),
+            ));
+        }
+        std::fs::remove_file(path)?;
+    }
+    // Bound inside a directory only this user can enter, then moved into place once it's 0600,
+    // so other users never get a chance to connect while its permissions are being tightened
+    let parent = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+        .unwrap_or(std::path::Path::new("."));
+    let staging = parent.join(format!(".rynx-control-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&staging);
+    std::fs::DirBuilder::new().mode(0o700).create(&staging)?;
+    let staged = staging.join("socket");
+    let bound = UnixListener::bind(&staged).and_then(|listener| {
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::rename(&staged, path)?;
+        Ok(listener)
+    });
+    let _ = std::fs::remove_dir_all(&staging);
+    let listener = bound?;
+    log::info!("Control socket listening on {}", path.display());
+
+    let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(serve_connection(stream, tx.clone()));
+                }
+                Err(e) => {
+                    log::warn!("Control socket stopped accepting: {}", e);
+                    return;
+                }
+            }
+        }
+    });
+    Ok(rx)
+}
+
+#[cfg(unix)]
+async fn serve_connection(stream: tokio::net::UnixStream, tx: mpsc::Sender<ControlRequest>) {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = match ControlCommand::parse(&line) {
+            Ok(command) => {
+                let (reply_tx, reply_rx) = oneshot::channel();
+                let request = ControlRequest {
+                    command,
+                    reply: reply_tx,
+                };
+                if tx.send(request).await.is_err() {
+                    return; // The browser is exiting
+                }
+                match reply_rx.await {
+                    Ok(reply) => reply,
+                    Err(_) => return,
+                }
+            }
+            Err(message) => reply_error(&message),
+        };
+        if writer.write_all(reply.as_bytes()).await.is_err() {
+            return;
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use crate::app::App;
+use crate::constants::UI_BORDER_WIDTH;
+use crate::event_handler::handle_network_event;
+use crate::network::NetworkResponse;
+use ratatui::backend::Backend;
+
+/// Fetch `url` the way the address bar would and return the page as plain text, wrapped at
+/// `width` columns, for `--dump`. Nothing here touches the terminal. A page that can't be
+/// shown (a failed request, a refused certificate, a login) is an error rather than the
+/// page the browser would show in its place.
+pub async fn dump_page<B: Backend>(mut app: App, url: &str, width: u16) -> Result<String, String> {
+    // Laid out as in a terminal just wide enough: `render_tab` takes off the block's borders
+    // and the renderer keeps a margin as wide again
+    let terminal_width = width.saturating_add(2 * UI_BORDER_WIDTH as u16);
+    app.current_tab().url_input = url.to_string();
+    app.submit_request();
+    while let Some(response) = app.rx.recv().await {
+        let failure = match &response {
+            NetworkResponse::Success(..) => None,
+            NetworkResponse::Error(_, message) => Some(message.clone()),
+            NetworkResponse::CertificateError(_, _, problem) => Some(problem.clone()),
+            NetworkResponse::AuthRequired(..) => Some(String::from("the page needs a login")),
+            NetworkResponse::PageTooLarge(_, _, limit) => {
+                Some(format!("the page is larger than {} bytes", limit))
+            }
+            NetworkResponse::JumpFailed(_, _, domain, _) => {
+                Some(format!("no jump service could find {}", domain))
+            }
+            // Loading notices and response headers on the way to the page
+            _ => {
+                handle_network_event::<B>(&mut app, response, terminal_width)
+                    .map_err(|e| e.to_string())?;
+                continue;
+            }
+        };
+        if let Some(message) = failure {
+            return Err(message);
+        }
+        handle_network_event::<B>(&mut app, response, terminal_width).map_err(|e| e.to_string())?;
+        let text = app.current_tab().page_text();
+        let lines: Vec<&str> = text.lines().map(str::trim_end).collect();
+        return Ok(lines.join("\n").trim_end().to_string());
+    }
+    Err(String::from("the request was dropped"))
+}
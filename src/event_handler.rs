@@ -1,29 +1,67 @@
-use crate::app::App;
-use crate::constants::{MOUSE_SCROLL_LINES, UI_HEIGHT_OFFSET, UI_ROW_OFFSET};
-use crate::models::{DownloadStatus, InputMode};
-use crate::network::NetworkResponse;
+use crate::app::{App, BrowserTab};
+use crate::config::AutoscrollMode;
+use crate::constants::MOUSE_SCROLL_LINES;
+use crate::keymap::{Action, EditAction};
+use crate::models::{
+    AuthPrompt, ConflictChoice, DownloadConflict, DownloadPrompt, DownloadStatus, InputMode,
+};
+use crate::network::{NetworkResponse, is_downloadable_file};
 use crate::constants::*;
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseButton, MouseEvent, MouseEventKind};
 use ratatui::backend::Backend;
+use ratatui::layout::{Margin, Position, Rect};
 use std::io::Result;
 
-/// Determines if a URL likely points to a downloadable file based on extension or patterns
-fn is_downloadable_file(url: &str) -> bool {
-    let u = url.to_lowercase();
-    // Restored common types that users expect to download via click
-    let binary_exts = [
-        "zip", "pdf", "exe", "dmg", "pkg", "deb", "iso", "mp4", "mp3",
-        "png", "jpg", "jpeg", "gif", "docx", "xlsx", "tar", "gz", "ogg", "txt"
-    ];
-
-    if let Some(dot) = u.rfind('.') {
-        let ext = u[dot + 1..].split('?').next().unwrap_or("");
-        if binary_exts.contains(&ext) { return true; }
+/// Clean clipboard text for a single-line input: drop ANSI escape sequences and every
+/// control character (newlines, tabs, ...), then trim surrounding whitespace
+fn sanitize_paste(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            if !c.is_control() {
+                out.push(c);
+            }
+            continue;
+        }
+        match chars.next() {
+            // CSI: parameters up to a final byte in '@'..='~'
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC: runs until BEL or ST (ESC \)
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            // Any other escape is a single following character
+            _ => {}
+        }
     }
+    out.trim().to_string()
+}
+
+/// Ctrl+V pastes into whatever is being typed: the address bar, a search, the link filter,
+/// a form field or the login popup
+fn is_paste_key(key: &KeyEvent) -> bool {
+    key.code == KeyCode::Char('v') && key.modifiers.contains(KeyModifiers::CONTROL)
+}
 
-    // Catch common dynamic download paths
-    ["/download/", "/files/", "/assets/", "/attachments/"].iter().any(|p| u.contains(p))
+/// The clipboard's text, cleaned up by `sanitize_paste`; None when it has none
+fn clipboard_paste(app: &mut App) -> Option<String> {
+    app.clipboard.get_text().ok().map(|text| sanitize_paste(&text))
 }
 
 pub fn handle_key_event<B: Backend>(
@@ -32,6 +70,19 @@ pub fn handle_key_event<B: Backend>(
     terminal_width: u16,
     terminal_height: u16,
 ) -> Result<bool> {
+    app.dirty = true;
+    if app.show_downloads {
+        return Ok(handle_download_manager(app, key));
+    }
+    if app.current_tab().auth_prompt.is_some() {
+        handle_auth_prompt(app, key);
+        return Ok(false);
+    }
+    if app.current_tab().download_prompt.is_some() {
+        handle_download_prompt(app, key);
+        return Ok(false);
+    }
+
     let active_mode = app.current_tab().input_mode;
 
     match active_mode {
@@ -39,6 +90,82 @@ pub fn handle_key_event<B: Backend>(
         InputMode::Editing => handle_editing_mode(app, key),
         InputMode::Visual => handle_visual_mode(app, key),
         InputMode::Search => handle_search_mode(app, key),
+        InputMode::FormInput => handle_form_input_mode(app, key, terminal_width, terminal_height),
+        InputMode::LinkFilter => handle_link_filter_mode(app, key, terminal_height),
+    }
+}
+
+/// Keys while the download manager popup is open; it captures all input until closed
+fn handle_download_manager(app: &mut App, key: KeyEvent) -> bool {
+    match key.code {
+        KeyCode::Esc | KeyCode::Char('J') | KeyCode::Char('q') => app.toggle_download_manager(),
+        KeyCode::Char('j') | KeyCode::Down => app.select_next_download(),
+        KeyCode::Char('k') | KeyCode::Up => app.select_prev_download(),
+        KeyCode::Char('x') | KeyCode::Delete => app.remove_selected_download(),
+        KeyCode::Char('c') => app.clear_finished_downloads(),
+        KeyCode::Enter => app.open_selected_download(),
+        _ => {}
+    }
+    false
+}
+
+/// Keys while the login popup is open: typing fills the active field, Enter moves on or submits
+fn handle_auth_prompt(app: &mut App, key: KeyEvent) {
+    let paste = if is_paste_key(&key) { clipboard_paste(app) } else { None };
+    let tab = app.current_tab();
+    let Some(prompt) = tab.auth_prompt.as_mut() else {
+        return;
+    };
+    let field = if prompt.editing_password {
+        &mut prompt.password
+    } else {
+        &mut prompt.username
+    };
+    match key.code {
+        KeyCode::Esc => {
+            tab.auth_prompt = None;
+            tab.set_transient_status("Login cancelled");
+        }
+        KeyCode::Tab | KeyCode::BackTab | KeyCode::Up | KeyCode::Down => {
+            prompt.editing_password = !prompt.editing_password;
+        }
+        KeyCode::Enter if !prompt.editing_password => prompt.editing_password = true,
+        KeyCode::Enter => app.submit_credentials(),
+        KeyCode::Backspace => {
+            field.pop();
+        }
+        KeyCode::Char('v') if is_paste_key(&key) => field.push_str(&paste.unwrap_or_default()),
+        KeyCode::Char(c) => field.push(c),
+        _ => {}
+    }
+}
+
+/// Keys while a download prompt is open. It captures all input until answered, so its
+/// answers can't be remapped and nothing else (`n` for a new tab, say) happens meanwhile.
+fn handle_download_prompt(app: &mut App, key: KeyEvent) {
+    let has_conflict = app
+        .current_tab()
+        .download_prompt
+        .as_ref()
+        .is_some_and(|p| p.conflict.is_some());
+    if has_conflict {
+        let choice = match key.code {
+            KeyCode::Char('o' | 'O') => ConflictChoice::Overwrite,
+            KeyCode::Char('r' | 'R') => ConflictChoice::Rename,
+            KeyCode::Char('c' | 'C' | 'n' | 'N') | KeyCode::Esc => ConflictChoice::Cancel,
+            _ => return,
+        };
+        app.resolve_download_conflict(choice);
+        return;
+    }
+    match key.code {
+        KeyCode::Char('y' | 'Y') => {
+            if let Some(prompt) = app.current_tab().download_prompt.take() {
+                app.trigger_download(prompt.url);
+            }
+        }
+        KeyCode::Char('n' | 'N') | KeyCode::Esc => app.current_tab().download_prompt = None,
+        _ => {}
     }
 }
 
@@ -48,37 +175,80 @@ fn handle_normal_mode<B: Backend>(
     terminal_width: u16,
     terminal_height: u16,
 ) -> Result<bool> {
-    match key.code {
+    // --- COUNT PREFIX (e.g. 5j) ---
+    // A leading zero isn't a count, so '0' only extends one that's already started
+    if let KeyCode::Char(c @ '0'..='9') = key.code
+        && (c != '0' || app.pending_count.is_some())
+        && !matches!(app.pending_action, Some(Action::SetMark | Action::JumpToMark))
+    {
+        let digit = c.to_digit(10).unwrap_or(0) as usize;
+        let count = app.pending_count.unwrap_or(0);
+        app.pending_count = Some((count * 10 + digit).min(MAX_MOTION_COUNT));
+        return Ok(false);
+    }
+    // Any other key consumes the count; keys that aren't motions simply discard it
+    let count = app.pending_count.take().unwrap_or(1);
+
+    // --- TWO-KEY SEQUENCES (e.g. yy) ---
+    // The first key waits for its partner; anything else cancels it
+    let pending_action = app.pending_action.take();
+    if let Some(prefix @ (Action::SetMark | Action::JumpToMark)) = pending_action
+        && let KeyCode::Char(name) = key.code
+    {
+        if prefix == Action::SetMark {
+            app.current_tab().set_mark(name);
+        } else {
+            let viewport_height = app.viewport_height(terminal_height);
+            app.current_tab().jump_to_mark(name, viewport_height);
+        }
+        return Ok(false);
+    }
+
+    let Some(action) = app.keymap.normal_action(&key) else {
+        return Ok(false);
+    };
+    match action {
+        Action::SetMark | Action::JumpToMark => app.pending_action = Some(action),
+        Action::CopyPageUrl => {
+            if pending_action == Some(Action::CopyPageUrl) {
+                let url = app.current_tab().url_input.clone();
+                let message = match app.clipboard.set_text(url) {
+                    Ok(_) => String::from("Page URL copied to clipboard!"),
+                    Err(e) => format!("Couldn't copy URL: {}", e),
+                };
+                app.current_tab().set_transient_status(message);
+            } else {
+                app.pending_action = Some(Action::CopyPageUrl);
+            }
+        }
+
         // --- VISUAL MODE ---
-        KeyCode::Char('v') => app.current_tab().enter_visual_mode(),
+        Action::VisualMode => app.current_tab().enter_visual_mode(),
 
         // --- DOWNLOAD ---
-        KeyCode::Char('d') => {
+        Action::Download => {
+            let download_dir = app.config.download_dir();
             let tab = app.current_tab();
             if let Some(region) = tab.link_regions.get(tab.selected_link_index) {
-                let url = crate::network::resolve_url(&tab.url_input, &region.url);
-                tab.initiate_download_request(url);
+                let url = tab.resolve_link(&region.url);
+                tab.initiate_download_request(url, &download_dir);
             }
         }
 
-        KeyCode::Char('y') | KeyCode::Char('Y') if app.current_tab().download_prompt.is_some() => {
-            if let Some(prompt) = app.current_tab().download_prompt.take() {
-                app.trigger_download(prompt.url);
-            }
+        Action::Dismiss if app.current_tab().pending_load.is_some() => {
+            app.current_tab().cancel_load();
         }
-
-        KeyCode::Char('n') | KeyCode::Char('N') if app.current_tab().download_prompt.is_some() => {
-            app.current_tab().download_prompt = None;
+        Action::Dismiss if app.current_tab().show_page_info => {
+            app.current_tab().show_page_info = false;
         }
-
-        KeyCode::Esc => {
+        Action::Dismiss => {
             let tab = app.current_tab();
 
             // Check if there is a download state to clear
             if let Some(state) = &tab.download_state {
                 match state.status {
                     // Only allow clearing if it's NOT actively downloading
-                    DownloadStatus::Completed | DownloadStatus::Failed(_) => {
+                    DownloadStatus::Completed | DownloadStatus::Failed(_) | DownloadStatus::Cancelled => {
                         tab.download_state = None; // This removes the data, so ui.rs stops rendering it
                         tab.status_message = String::from("Ready");
                     }
@@ -86,34 +256,83 @@ fn handle_normal_mode<B: Backend>(
                 }
             }
         }
+        Action::CopyLink => {
+            let tab = app.current_tab();
+            if let Some(region) = tab.link_regions.get(tab.selected_link_index) {
+                let full_url = tab.resolve_link(&region.url);
+                app.copy_link_url(full_url);
+            }
+        }
+        Action::PageInfo => {
+            let tab = app.current_tab();
+            tab.show_page_info = !tab.show_page_info;
+        }
+        Action::DownloadManager => app.toggle_download_manager(),
+        Action::CycleUserAgent => app.cycle_user_agent(),
+        Action::FocusInput => {
+            let viewport_height = app.viewport_height(terminal_height);
+            let tab = app.current_tab();
+            if tab.focus_next_input() {
+                scroll_to_focused_input(tab, viewport_height);
+            } else {
+                tab.set_transient_status("No form fields on this page");
+            }
+        }
+        Action::CancelDownload => {
+            let active = app
+                .current_tab()
+                .download_state
+                .as_ref()
+                .filter(|d| d.status == DownloadStatus::Active)
+                .map(|d| d.id);
+            if let Some(id) = active {
+                app.cancel_download(id);
+            }
+        }
+
         // --- TAB CONTROLS ---
-        KeyCode::Char('n') => app.add_tab(None),
-        KeyCode::Char('t') => {
+        Action::NewTab => app.add_tab(None),
+        Action::OpenInBackgroundTab => {
+            let tab = app.current_tab();
+            if let Some(region) = tab.link_regions.get(tab.selected_link_index) {
+                let full_url = tab.resolve_link(&region.url);
+                app.open_link_in_background_tab(full_url);
+            }
+        }
+        Action::OpenInNewTab => {
             let tab = app.current_tab();
             if let Some(region) = tab.link_regions.get(tab.selected_link_index) {
-                let full_url = crate::network::resolve_url(&tab.url_input, &region.url);
+                let full_url = tab.resolve_link(&region.url);
                 app.open_link_in_new_tab(full_url);
             }
         }
-        KeyCode::Char('w') => app.close_tab(),
-        KeyCode::Char(']') => {
-            app.active_tab_index = (app.active_tab_index + 1) % app.tabs.len();
+        Action::CloseTab => {
+            app.close_tab();
+            app.select_tab(app.active_tab_index, terminal_height);
+        }
+        Action::CloseOtherTabs => app.close_other_tabs(),
+        Action::CloseTabsToRight => app.close_tabs_to_right(),
+        Action::NextTab => {
+            let next = (app.active_tab_index + 1) % app.tabs.len();
+            app.select_tab(next, terminal_height);
         }
-        KeyCode::Char('[') => {
-            if app.active_tab_index > 0 {
-                app.active_tab_index -= 1;
+        Action::PreviousTab => {
+            let previous = if app.active_tab_index > 0 {
+                app.active_tab_index - 1
             } else {
-                app.active_tab_index = app.tabs.len() - 1;
-            }
+                app.tabs.len() - 1
+            };
+            app.select_tab(previous, terminal_height);
         }
 
         // --- PAGE CONTROLS (Targeting current_tab) ---
-        KeyCode::Char('q') => return Ok(true), // Signal to quit
-        KeyCode::Char('e') => {
+        Action::Quit => return Ok(true), // Signal to quit
+        Action::EditUrl => {
             app.current_tab().input_mode = InputMode::Editing;
+            app.current_tab().move_url_cursor_to_end();
             app.current_tab().status_message = String::from("EDIT MODE - Type URL and press Enter");
         }
-        KeyCode::Char('/') => {
+        Action::Search => {
             app.current_tab().input_mode = InputMode::Search;
             app.current_tab().search_state = Some(crate::models::SearchState {
                 query: String::new(),
@@ -123,49 +342,33 @@ fn handle_normal_mode<B: Backend>(
             app.current_tab().status_message =
                 String::from("SEARCH MODE - Type query and press Enter");
         }
-        KeyCode::Char('>') => {
+        Action::FilterLinks => {
             let tab = app.current_tab();
-            tab.next_search_match();
-            // Auto-scroll to the current search match
-            if let Some(search_state) = &tab.search_state {
-                if let Some(current_match) =
-                    search_state.matches.get(search_state.current_match_index)
-                {
-                    let viewport_height = terminal_height.saturating_sub(UI_HEIGHT_OFFSET) as usize;
-
-                    if current_match.line_index < tab.scroll {
-                        // If match is above current view, jump to it
-                        tab.scroll = current_match.line_index;
-                    } else if current_match.line_index >= tab.scroll + viewport_height {
-                        // If match is below, scroll just enough to make it visible at the bottom
-                        tab.scroll = current_match.line_index - viewport_height + 1;
-                    }
-                }
+            if tab.link_regions.is_empty() {
+                tab.set_transient_status("No links on this page");
+            } else {
+                tab.start_link_filter();
             }
         }
-        KeyCode::Char('<') => {
+        Action::NextMatch => {
+            let autoscroll = app.config.autoscroll;
+            let viewport_height = app.viewport_height(terminal_height);
+            let tab = app.current_tab();
+            tab.next_search_match();
+            scroll_to_search_match(tab, viewport_height, autoscroll);
+        }
+        Action::PreviousMatch => {
+            let autoscroll = app.config.autoscroll;
+            let viewport_height = app.viewport_height(terminal_height);
             let tab = app.current_tab();
             tab.previous_search_match();
-            // Auto-scroll to the current search match
-            if let Some(search_state) = &tab.search_state {
-                if let Some(current_match) =
-                    search_state.matches.get(search_state.current_match_index)
-                {
-                    let viewport_height = terminal_height.saturating_sub(UI_HEIGHT_OFFSET) as usize;
-
-                    if current_match.line_index < tab.scroll {
-                        // If match is above current view, jump to it
-                        tab.scroll = current_match.line_index;
-                    } else if current_match.line_index >= tab.scroll + viewport_height {
-                        // If match is below, scroll just enough to make it visible at the bottom
-                        tab.scroll = current_match.line_index - viewport_height + 1;
-                    }
-                }
-            }
+            scroll_to_search_match(tab, viewport_height, autoscroll);
+        }
+        Action::ScrollDown => {
+            app.current_tab().scroll = app.current_tab().scroll.saturating_add(count)
         }
-        KeyCode::Down => app.current_tab().scroll = app.current_tab().scroll.saturating_add(1),
-        KeyCode::Up => app.current_tab().scroll = app.current_tab().scroll.saturating_sub(1),
-        KeyCode::Char('V') => {
+        Action::ScrollUp => app.current_tab().scroll = app.current_tab().scroll.saturating_sub(count),
+        Action::ToggleSource => {
             let active_index = app.active_tab_index;
             let tab = app.current_tab();
             tab.is_source_view = !tab.is_source_view; // Toggle
@@ -177,15 +380,16 @@ fn handle_normal_mode<B: Backend>(
                 String::from("Viewing Rendered")
             };
 
-            // Re-render immediately
+            // Re-render immediately, then search the content that's now showing
             app.render_tab(active_index, terminal_width);
+            app.current_tab().refresh_search();
         }
 
         // --- VISUAL NAV ---
-        KeyCode::Char('h') => {
+        Action::CursorLeft => {
             app.current_tab().cursor_char = app.current_tab().cursor_char.saturating_sub(1)
         }
-        KeyCode::Char('l') => {
+        Action::CursorRight => {
             let tab = app.current_tab();
             let line_len = tab
                 .rendered_content
@@ -194,40 +398,66 @@ fn handle_normal_mode<B: Backend>(
                 .unwrap_or(0);
             tab.cursor_char = (tab.cursor_char + 1).min(line_len);
         }
-        KeyCode::Char('k') => {
+        Action::CursorUp => {
             let tab = app.current_tab();
-            tab.cursor_line = tab.cursor_line.saturating_sub(1);
+            tab.cursor_line = tab.cursor_line.saturating_sub(count);
             // Auto-scroll up if cursor goes off-screen
             if tab.cursor_line < tab.scroll {
                 tab.scroll = tab.cursor_line;
             }
         }
-        KeyCode::Char('j') => {
+        Action::CursorDown => {
+            let viewport_height = app.viewport_height(terminal_height);
             let tab = app.current_tab();
             let max_lines = tab.rendered_content.len().saturating_sub(1);
-            tab.cursor_line = (tab.cursor_line + 1).min(max_lines);
+            tab.cursor_line = (tab.cursor_line + count).min(max_lines);
 
             // Auto-scroll down if cursor goes off-screen
-            let viewport_height = terminal_height.saturating_sub(UI_HEIGHT_OFFSET) as usize;
             if tab.cursor_line >= tab.scroll + viewport_height {
                 tab.scroll = tab.cursor_line - viewport_height + 1;
             }
         }
 
-        // HISTORY BACK
-        KeyCode::Backspace | KeyCode::Left => {
-            let tab = app.current_tab();
-            if let Some(previous_url) = tab.history.pop() {
-                tab.url_input = previous_url;
-                app.submit_request();
-            }
+        // PAGE VIEW AND TAB COMMANDS
+        Action::ToggleWrap => app.toggle_wrap(terminal_width),
+        Action::ReaderMode => app.toggle_reader_mode(terminal_width),
+        Action::ParentDirectory => app.go_to_parent(),
+        Action::OpenFeed => app.open_feed(),
+        Action::TrustCertificate => app.trust_certificate(),
+        Action::ToggleSplit => app.toggle_split(),
+        Action::SwitchPane => app.switch_pane(),
+
+        // COLUMN WIDTH (per tab, saved with the session)
+        Action::NarrowColumn => {
+            let columns = (COLUMN_WIDTH_STEP * count) as isize;
+            app.adjust_column_width(-columns, terminal_width);
+        }
+        Action::WidenColumn => {
+            let columns = (COLUMN_WIDTH_STEP * count) as isize;
+            app.adjust_column_width(columns, terminal_width);
         }
+        Action::ResetColumnWidth => app.reset_column_width(terminal_width),
+
+        // HORIZONTAL SCROLL (source view and other wide lines)
+        Action::ScrollRight => app.current_tab().scroll_horizontally(
+            HORIZONTAL_SCROLL_COLUMNS as isize,
+            content_width(terminal_width),
+        ),
+        Action::ScrollLeft => app.current_tab().scroll_horizontally(
+            -(HORIZONTAL_SCROLL_COLUMNS as isize),
+            content_width(terminal_width),
+        ),
+
+        // HISTORY BACK
+        Action::Back => app.go_back(),
 
         // LINK NAVIGATION (Tab)
-        KeyCode::Tab | KeyCode::BackTab => {
+        Action::NextLink | Action::PreviousLink => {
+            let autoscroll = app.config.autoscroll;
+            let viewport_height = app.viewport_height(terminal_height);
             let tab = app.current_tab();
             if !tab.link_regions.is_empty() {
-                if key.code == KeyCode::Tab {
+                if action == Action::NextLink {
                     tab.selected_link_index =
                         (tab.selected_link_index + 1) % tab.link_regions.len();
                 } else {
@@ -239,101 +469,267 @@ fn handle_normal_mode<B: Backend>(
                     };
                 }
 
-                // --- IMPROVED AUTOSCROLL ---
-                let selected = &tab.link_regions[tab.selected_link_index];
-                // We subtract 6 for the Tab bar (3) and URL bar (3),
-                // and another 2 for the borders of the Browser block.
-                let viewport_height = terminal_height.saturating_sub(UI_HEIGHT_OFFSET) as usize;
-
-                if selected.line_index < tab.scroll {
-                    // If link is above current view, jump to it
-                    tab.scroll = selected.line_index;
-                } else if selected.line_index >= tab.scroll + viewport_height {
-                    // If link is below, scroll just enough to make it visible at the bottom
-                    tab.scroll = selected.line_index - viewport_height + 1;
-                }
+                let line_index = tab.link_regions[tab.selected_link_index].line_index;
+                reveal_line(tab, line_index, viewport_height, autoscroll);
             }
         }
 
         // LINK SELECTION (Enter)
-        KeyCode::Enter => {
-            let tab = app.current_tab();
-
-            if let Some(region) = tab.link_regions.get(tab.selected_link_index) {
-                // 1. Save History
-                if !tab.url_input.is_empty() {
-                    tab.history.push(tab.url_input.clone());
-                }
-
-                // 2. Resolve URL (Handle relative paths)
-                let new_url = crate::network::resolve_url(&tab.url_input, &region.url);
-                tab.url_input = new_url;
-
-                // 3. Submit
-                app.submit_request(); // This function already looks at current_tab()
-
-                // 4. Reset
-                app.current_tab().selected_link_index = 0;
-            }
-        }
-        KeyCode::Char('p') => {
+        Action::FollowLink => follow_selected_link(app),
+        Action::ToggleI2p => {
             app.i2p_mode = !app.i2p_mode; // Toggle
         }
-        _ => {}
     }
     Ok(false)
 }
 
 fn handle_editing_mode(app: &mut App, key: KeyEvent) -> Result<bool> {
-    match key.code {
-        KeyCode::Enter => {
+    let Some(action) = app.keymap.editing_action(&key) else {
+        if let KeyCode::Char(c) = key.code {
+            app.current_tab().insert_url_text(c.encode_utf8(&mut [0; 4]));
+        }
+        return Ok(false);
+    };
+    match action {
+        EditAction::Submit => {
+            let typed = app.current_tab().url_input.clone();
+            if app.run_download_command(&typed)
+                || app.run_user_agent_command(&typed)
+                || app.run_pipe_command(&typed)
+            {
+                // A command isn't an address: the bar goes back to the page on screen, so it
+                // isn't copied, resolved against or saved in place of the page
+                let tab = app.current_tab();
+                tab.url_input = tab.loaded_url.clone();
+                tab.move_url_cursor_to_end();
+                tab.input_mode = InputMode::Normal;
+                return Ok(false);
+            }
+
+            let limit = app.config.max_history;
             let tab = app.current_tab();
             // Save history
             if !tab.url_input.is_empty() {
-                tab.history.push(tab.url_input.clone());
+                tab.push_history(tab.url_input.clone(), limit);
             }
 
             app.submit_request();
             app.current_tab().input_mode = InputMode::Normal;
         }
         // COPY LINE (from address bar to clipboard)
-        KeyCode::Char('y') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+        EditAction::CopyAddress => {
             let current_input = app.current_tab().url_input.clone();
             if let Ok(_) = app.clipboard.set_text(current_input) {
-                app.current_tab().status_message = String::from("Address copied to clipboard!");
+                app.current_tab().set_transient_status("Address copied to clipboard!");
             }
         }
         // CLEAR LINE (Standard Terminal Shortcut)
-        KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-            app.current_tab().url_input.clear();
+        EditAction::ClearLine => {
+            app.current_tab().clear_url_input();
         }
 
+        // DELETE WORD (Readline Ctrl+W / Alt+Backspace)
+        EditAction::DeleteWord => app.current_tab().delete_url_word(),
+
+        // LINE START / END (Readline Ctrl+A / Ctrl+E)
+        EditAction::LineStart => app.current_tab().move_url_cursor_to_start(),
+        EditAction::LineEnd => app.current_tab().move_url_cursor_to_end(),
+        EditAction::CursorLeft => app.current_tab().move_url_cursor_left(),
+        EditAction::CursorRight => app.current_tab().move_url_cursor_right(),
+
         // PASTE (Standard Shortcut)
-        KeyCode::Char('v') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-            if let Ok(text) = app.clipboard.get_text() {
-                // Sanitize to remove newlines for the address bar
-                let sanitized = text.replace(|c: char| c == '\n' || c == '\r', "");
-                app.current_tab().url_input.push_str(&sanitized);
+        EditAction::Paste => {
+            if let Some(text) = clipboard_paste(app) {
+                app.current_tab().insert_url_text(&text);
             }
         }
 
         // COMBINED: CLEAR AND PASTE (Using Ctrl + K)
-        KeyCode::Char('k') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-            app.current_tab().url_input.clear();
-            if let Ok(text) = app.clipboard.get_text() {
-                let sanitized = text.replace(|c: char| c == '\n' || c == '\r', "");
-                app.current_tab().url_input.push_str(&sanitized);
+        EditAction::ClearAndPaste => {
+            app.current_tab().clear_url_input();
+            if let Some(text) = clipboard_paste(app) {
+                app.current_tab().insert_url_text(&text);
             }
         }
-        KeyCode::Char(c) => {
-            app.current_tab().url_input.push(c);
+        EditAction::DeleteChar => {
+            app.current_tab().delete_url_char();
+        }
+        EditAction::Cancel => {
+            app.current_tab().input_mode = InputMode::Normal;
+            app.current_tab().status_message = String::from("Ready");
+        }
+    }
+    Ok(false)
+}
+
+/// Open the selected link of the current tab
+fn follow_selected_link(app: &mut App) {
+    let tab = app.current_tab();
+
+    if let Some(region) = tab.link_regions.get(tab.selected_link_index) {
+        // 1. Resolve URL (Handle relative paths)
+        let new_url = tab.resolve_link(&region.url);
+        if app.handle_non_web_link(&new_url) {
+            return;
+        }
+
+        // 2. Navigate or download
+        app.follow_link(new_url);
+    }
+}
+
+/// Keys while filtering links: typing narrows them, Tab cycles the matches, Enter follows one
+fn handle_link_filter_mode(app: &mut App, key: KeyEvent, terminal_height: u16) -> Result<bool> {
+    let autoscroll = app.config.autoscroll;
+    let viewport_height = app.viewport_height(terminal_height);
+    let paste = if is_paste_key(&key) { clipboard_paste(app) } else { None };
+    let tab = app.current_tab();
+    match key.code {
+        KeyCode::Esc => {
+            tab.end_link_filter(false);
+            tab.status_message = String::from("Ready");
+        }
+        KeyCode::Enter if !tab.link_regions.is_empty() => {
+            tab.end_link_filter(true);
+            follow_selected_link(app);
+        }
+        KeyCode::Tab | KeyCode::BackTab | KeyCode::Down | KeyCode::Up
+            if !tab.link_regions.is_empty() =>
+        {
+            let count = tab.link_regions.len();
+            tab.selected_link_index = if matches!(key.code, KeyCode::Tab | KeyCode::Down) {
+                (tab.selected_link_index + 1) % count
+            } else {
+                (tab.selected_link_index + count - 1) % count
+            };
+            let line_index = tab.link_regions[tab.selected_link_index].line_index;
+            reveal_line(tab, line_index, viewport_height, autoscroll);
         }
         KeyCode::Backspace => {
-            app.current_tab().url_input.pop();
+            if let Some(filter) = &mut tab.link_filter {
+                filter.query.pop();
+            }
+            tab.apply_link_filter();
+        }
+        KeyCode::Char(c) => {
+            if let Some(filter) = &mut tab.link_filter {
+                match &paste {
+                    Some(text) => filter.query.push_str(text),
+                    None if is_paste_key(&key) => {}
+                    None => filter.query.push(c),
+                }
+            }
+            tab.apply_link_filter();
+            // Even a single match waits for Enter, so keys typed after it stay in the filter
+            // instead of reaching normal mode
+            if let Some(region) = tab.link_regions.first() {
+                let line_index = region.line_index;
+                reveal_line(tab, line_index, viewport_height, autoscroll);
+            }
+        }
+        _ => {}
+    }
+    Ok(false)
+}
+
+/// Columns available to page content inside the block borders
+fn content_width(terminal_width: u16) -> usize {
+    (terminal_width as usize).saturating_sub(UI_BORDER_WIDTH)
+}
+
+/// Bring an off-screen line into view, at the nearest edge or centered per the config
+fn reveal_line(
+    tab: &mut BrowserTab,
+    line_index: usize,
+    viewport_height: usize,
+    autoscroll: AutoscrollMode,
+) {
+    if line_index >= tab.scroll && line_index < tab.scroll + viewport_height {
+        return;
+    }
+    tab.scroll = match autoscroll {
+        AutoscrollMode::Center => line_index.saturating_sub(viewport_height / 2),
+        // Above the view it lands on the top row; below it, just enough to show it at the bottom
+        AutoscrollMode::Edge if line_index < tab.scroll => line_index,
+        AutoscrollMode::Edge => line_index + 1 - viewport_height,
+    };
+}
+
+/// Auto-scroll to the current search match
+fn scroll_to_search_match(
+    tab: &mut BrowserTab,
+    viewport_height: usize,
+    autoscroll: AutoscrollMode,
+) {
+    let line_index = tab
+        .search_state
+        .as_ref()
+        .and_then(|search| search.matches.get(search.current_match_index))
+        .map(|current| current.line_index);
+    if let Some(line_index) = line_index {
+        reveal_line(tab, line_index, viewport_height, autoscroll);
+    }
+}
+
+/// Keep the focused form field inside the viewport
+fn scroll_to_focused_input(tab: &mut BrowserTab, viewport_height: usize) {
+    let Some(region) = tab.focused_input.and_then(|i| tab.input_regions.get(i)) else {
+        return;
+    };
+    let line_index = region.line_index;
+    if line_index < tab.scroll {
+        tab.scroll = line_index;
+    } else if line_index >= tab.scroll + viewport_height {
+        tab.scroll = line_index - viewport_height + 1;
+    }
+}
+
+fn handle_form_input_mode(
+    app: &mut App,
+    key: KeyEvent,
+    terminal_width: u16,
+    terminal_height: u16,
+) -> Result<bool> {
+    let active_index = app.active_tab_index;
+    match key.code {
+        KeyCode::Enter => {
+            let tab = app.current_tab();
+            if let Some(region) = tab.focused_input.and_then(|i| tab.input_regions.get(i)) {
+                let form_index = region.form_index;
+                app.submit_form(form_index);
+            }
+        }
+        KeyCode::Tab => {
+            let viewport_height = app.viewport_height(terminal_height);
+            let tab = app.current_tab();
+            tab.focus_next_input();
+            scroll_to_focused_input(tab, viewport_height);
         }
         KeyCode::Esc => {
-            app.current_tab().input_mode = InputMode::Normal;
-            app.current_tab().status_message = String::from("Ready");
+            let tab = app.current_tab();
+            tab.input_mode = InputMode::Normal;
+            tab.focused_input = None;
+            tab.status_message = String::from("Ready");
+        }
+        KeyCode::Char('v') if is_paste_key(&key) => {
+            if let Some(text) = clipboard_paste(app)
+                && let Some(field) = app.current_tab().focused_field_mut()
+            {
+                field.value.push_str(&text);
+                app.render_tab(active_index, terminal_width);
+            }
+        }
+        KeyCode::Char(c) => {
+            if let Some(field) = app.current_tab().focused_field_mut() {
+                field.value.push(c);
+                app.render_tab(active_index, terminal_width);
+            }
+        }
+        KeyCode::Backspace => {
+            if let Some(field) = app.current_tab().focused_field_mut() {
+                field.value.pop();
+                app.render_tab(active_index, terminal_width);
+            }
         }
         _ => {}
     }
@@ -344,6 +740,7 @@ fn handle_visual_mode(app: &mut App, key: KeyEvent) -> Result<bool> {
     match key.code {
         KeyCode::Char('h') => {
             let tab = app.current_tab();
+            tab.clamp_cursor();
             tab.cursor_char = tab.cursor_char.saturating_sub(1);
             if let Some(ref mut sel) = tab.selection {
                 sel.end_char = tab.cursor_char;
@@ -356,8 +753,7 @@ fn handle_visual_mode(app: &mut App, key: KeyEvent) -> Result<bool> {
             tab.cursor_line = (tab.cursor_line + 1).min(max_lines);
 
             // Ensure cursor_char is valid for the new line
-            let line_len = tab.rendered_content[tab.cursor_line].width();
-            tab.cursor_char = tab.cursor_char.min(line_len);
+            tab.clamp_cursor();
 
             if let Some(ref mut sel) = tab.selection {
                 sel.end_line = tab.cursor_line;
@@ -368,9 +764,7 @@ fn handle_visual_mode(app: &mut App, key: KeyEvent) -> Result<bool> {
         KeyCode::Char('k') => {
             let tab = app.current_tab();
             tab.cursor_line = tab.cursor_line.saturating_sub(1);
-
-            let line_len = tab.rendered_content[tab.cursor_line].width();
-            tab.cursor_char = tab.cursor_char.min(line_len);
+            tab.clamp_cursor();
 
             if let Some(ref mut sel) = tab.selection {
                 sel.end_line = tab.cursor_line;
@@ -380,8 +774,8 @@ fn handle_visual_mode(app: &mut App, key: KeyEvent) -> Result<bool> {
         // MOVE RIGHT
         KeyCode::Char('l') => {
             let tab = app.current_tab();
-            let line_len = tab.rendered_content[tab.cursor_line].width();
-            tab.cursor_char = (tab.cursor_char + 1).min(line_len);
+            tab.clamp_cursor();
+            tab.cursor_char = (tab.cursor_char + 1).min(tab.cursor_line_width());
 
             if let Some(ref mut sel) = tab.selection {
                 sel.end_char = tab.cursor_char;
@@ -399,7 +793,7 @@ fn handle_visual_mode(app: &mut App, key: KeyEvent) -> Result<bool> {
 
                 // 3. Re-borrow the tab to update status and reset mode
                 let tab = app.current_tab();
-                tab.status_message = String::from("Text yanked to clipboard!");
+                tab.set_transient_status("Text yanked to clipboard!");
                 tab.input_mode = InputMode::Normal;
                 tab.selection = None;
             } else {
@@ -418,12 +812,49 @@ fn handle_visual_mode(app: &mut App, key: KeyEvent) -> Result<bool> {
     Ok(false)
 }
 
+/// Map a mouse position to a (page line, page column) inside the focused pane, if it's there.
+/// The pane's borders and the blank rows below the last line of the page don't count.
+fn content_position(mouse: &MouseEvent, pane: Rect, tab: &BrowserTab) -> Option<(usize, usize)> {
+    let inner = pane.inner(Margin::new(1, 1));
+    if !inner.contains(Position::new(mouse.column, mouse.row)) {
+        return None;
+    }
+    let line = (mouse.row - inner.y) as usize + tab.scroll;
+    if line >= tab.rendered_content.len() {
+        return None;
+    }
+    // `h_scroll` columns are hidden off the left edge
+    let column = (mouse.column - inner.x) as usize + tab.h_scroll;
+    Some((line, column))
+}
+
+/// Resolve the URL of the link region covering the given page position
+fn link_url_at(tab: &BrowserTab, line_index: usize, column: usize) -> Option<String> {
+    tab.link_regions
+        .iter()
+        .find(|link| link.line_index == line_index && column >= link.x_start && column < link.x_end)
+        .map(|region| tab.resolve_link(&region.url))
+}
+
 pub fn handle_mouse_event<B: Backend>(
     app: &mut App,
     mouse: MouseEvent,
     terminal_width: u16,
     terminal_height: u16,
 ) -> Result<()> {
+    app.dirty = true;
+    // Clicking or scrolling in the other pane of a split view moves the focus there first
+    let lands_in_other_pane = matches!(
+        mouse.kind,
+        MouseEventKind::Down(_) | MouseEventKind::ScrollDown | MouseEventKind::ScrollUp
+    ) && app
+        .panes(terminal_width, terminal_height)
+        .1
+        .is_some_and(|other| other.contains(Position::new(mouse.column, mouse.row)));
+    if lands_in_other_pane && app.tabs[app.active_tab_index].download_prompt.is_none() {
+        app.switch_pane();
+    }
+    let pane = app.panes(terminal_width, terminal_height).0;
     let tab = app.current_tab();
     match mouse.kind {
         MouseEventKind::ScrollDown => {
@@ -433,6 +864,10 @@ pub fn handle_mouse_event<B: Backend>(
             tab.scroll = tab.scroll.saturating_sub(MOUSE_SCROLL_LINES); // Scroll up by configured amount
         }
         MouseEventKind::Down(MouseButton::Left) => {
+            // The UI only shows a "too small" notice, so there is nothing to click
+            if terminal_height < MIN_TERMINAL_HEIGHT {
+                return Ok(());
+            }
             if let Some(prompt) = tab.download_prompt.take() {
                 let popup_x = terminal_width / DOWNLOAD_PROMPT_X_DIVISOR;
                 let popup_y = (terminal_height / DOWNLOAD_PROMPT_Y_DIVISOR).saturating_sub(DOWNLOAD_PROMPT_Y_OFFSET);
@@ -444,7 +879,17 @@ pub fn handle_mouse_event<B: Backend>(
                 {
                     // Detect clicks on the button line (popup_y + offset)
                     if mouse.row == popup_y + DOWNLOAD_PROMPT_BUTTON_ROW_OFFSET {
-                        if mouse.column < popup_x + (popup_w / 2) {
+                        if prompt.conflict.is_some() {
+                            // Three buttons: Overwrite | Rename | Cancel
+                            let third = (mouse.column - popup_x) / (popup_w / 3).max(1);
+                            let choice = match third {
+                                0 => ConflictChoice::Overwrite,
+                                1 => ConflictChoice::Rename,
+                                _ => ConflictChoice::Cancel,
+                            };
+                            tab.download_prompt = Some(prompt);
+                            app.resolve_download_conflict(choice);
+                        } else if mouse.column < popup_x + (popup_w / 2) {
                             app.trigger_download(prompt.url);
                         } else {
                             tab.download_prompt = None;
@@ -457,116 +902,411 @@ pub fn handle_mouse_event<B: Backend>(
                 tab.download_prompt = Some(prompt);
             }
             // 1. Determine which line was clicked
-            if mouse.row >= UI_ROW_OFFSET {
-                // UI_ROW_OFFSET is the UI offset
-                let visual_line = (mouse.row - UI_ROW_OFFSET) as usize;
-                let real_line_idx = visual_line + tab.scroll;
-                let click_x = (mouse.column as usize).saturating_sub(1);
-
+            let position = content_position(&mouse, pane, tab);
+            if let Some((real_line_idx, click_x)) = position {
                 tab.cursor_line = real_line_idx;
                 tab.cursor_char = click_x;
 
-                // 2. Search the Link Regions for a match
-                // We filter for links on this specific line
-                let found_link = tab.link_regions.iter().find(|link| {
-                    link.line_index == real_line_idx
-                        && click_x >= link.x_start
-                        && click_x < link.x_end
+                // A fresh click drops any previous mouse selection
+                if tab.input_mode == InputMode::Visual {
+                    tab.input_mode = InputMode::Normal;
+                    tab.selection = None;
+                }
+                tab.drag_anchor = None;
+
+                // A click on a form field focuses it for typing
+                let clicked_input = tab.input_regions.iter().position(|input| {
+                    input.line_index == real_line_idx
+                        && click_x >= input.x_start
+                        && click_x < input.x_end
                 });
+                if let Some(input_index) = clicked_input {
+                    tab.focus_input(input_index);
+                    return Ok(());
+                }
+                if tab.input_mode == InputMode::FormInput {
+                    tab.input_mode = InputMode::Normal;
+                    tab.focused_input = None;
+                }
 
-                if let Some(region) = found_link {
+                // 2. Search the Link Regions for a match
+                let found_link = link_url_at(tab, real_line_idx, click_x);
+                if found_link.is_none() {
+                    // Not a link: this may be the start of a drag selection
+                    tab.drag_anchor = Some((real_line_idx, click_x));
+                }
+                if let Some(full_url) = found_link {
                     // 3. Determine if this should be a download or navigation
-                    let full_url = crate::network::resolve_url(&tab.url_input, &region.url);
-
                     if mouse.modifiers.contains(KeyModifiers::CONTROL) {
                         app.open_link_in_new_tab(full_url);
-                    } else if is_downloadable_file(&full_url) {
-                        // download for file types
-                        tab.initiate_download_request(full_url);
+                    } else if mouse.modifiers.contains(KeyModifiers::ALT) {
+                        app.copy_link_url(full_url);
+                    } else if app.handle_non_web_link(&full_url) {
+                        // mailto:/tel:/javascript: links never navigate
                     } else {
-                        // Normal navigation for HTML pages
-                        if !tab.url_input.is_empty() {
-                            tab.history.push(tab.url_input.clone());
-                        }
-                        tab.url_input = full_url;
-                        app.submit_request();
+                        app.follow_link(full_url);
                     }
                 }
             }
         }
+        MouseEventKind::Drag(MouseButton::Left) => {
+            let position = content_position(&mouse, pane, tab);
+            if let (Some((anchor_line, anchor_char)), Some((line, drag_x))) =
+                (tab.drag_anchor, position)
+            {
+                // Holding still keeps this a plain click
+                if (line, drag_x) != (anchor_line, anchor_char) {
+                    tab.cursor_line = line;
+                    tab.cursor_char = drag_x;
+                    tab.selection = Some(crate::models::Selection {
+                        start_line: anchor_line,
+                        start_char: anchor_char,
+                        end_line: line,
+                        end_char: drag_x,
+                    });
+                    tab.input_mode = InputMode::Visual;
+                }
+            }
+        }
+        MouseEventKind::Up(MouseButton::Left) => {
+            let was_dragging = tab.drag_anchor.take().is_some();
+            if was_dragging && tab.selection.is_some() {
+                tab.status_message = String::from("VISUAL MODE - 'y' to copy selection");
+            }
+        }
+        MouseEventKind::Down(MouseButton::Middle) => {
+            if terminal_height < MIN_TERMINAL_HEIGHT {
+                return Ok(());
+            }
+            // Middle-click is the conventional "open in new tab" gesture
+            let position = content_position(&mouse, pane, tab);
+            if let Some((real_line_idx, click_x)) = position
+                && let Some(full_url) = link_url_at(tab, real_line_idx, click_x)
+            {
+                app.open_link_in_new_tab(full_url);
+            }
+        }
         // Optional: can handle clicks here too!
         _ => {}
     }
     Ok(())
 }
 
+/// Events can still be queued after a cancel; those are dropped once the entry is settled
+fn download_is_active(app: &App, download_id: usize) -> bool {
+    app.downloads
+        .get(&download_id)
+        .is_some_and(|d| d.status == DownloadStatus::Active)
+}
+
+/// Mirror a download's app-wide entry into the footer of the tab that started it
+fn sync_tab_download(app: &mut App, tab_id: usize, download_id: usize, status: String) {
+    let entry = app.downloads.get(&download_id).cloned();
+    if let Some(tab) = app.tabs.iter_mut().find(|t| t.id == tab_id) {
+        tab.download_state = entry;
+        tab.status_message = status;
+    }
+}
+
 pub fn handle_network_event<B: Backend>(
     app: &mut App,
     response: NetworkResponse,
     terminal_width: u16,
 ) -> Result<()> {
+    app.dirty = true;
+    // Downloads are tracked app-wide so they survive tab switches and closes
+    let response = match response {
+        NetworkResponse::DownloadProgress(tab_id, download_id, downloaded, total) => {
+            if !download_is_active(app, download_id) {
+                return Ok(());
+            }
+            if let Some(d) = app.downloads.get_mut(&download_id) {
+                d.bytes_downloaded = downloaded;
+                d.total_size = total;
+                d.last_update = std::time::Instant::now();
+            }
+            let status = match total {
+                Some(t) if t > 0 => format!("Downloading: {}%", (downloaded * 100) / t),
+                _ => format!("Downloading: {} bytes", downloaded),
+            };
+            sync_tab_download(app, tab_id, download_id, status);
+            return Ok(());
+        }
+        NetworkResponse::DownloadFinished(tab_id, download_id, path) => {
+            if !download_is_active(app, download_id) {
+                return Ok(());
+            }
+            if let Some(d) = app.downloads.get_mut(&download_id) {
+                d.status = DownloadStatus::Completed;
+                d.filename = path.clone();
+                d.saved_path = Some(path.clone());
+                d.last_update = std::time::Instant::now();
+            }
+            let verified = app.downloads.get(&download_id).is_some_and(|d| d.expected_sha256.is_some());
+            let status = if verified {
+                format!("Download complete, SHA-256 verified: {}", path)
+            } else {
+                format!("Download complete: {}", path)
+            };
+            sync_tab_download(app, tab_id, download_id, status);
+            return Ok(());
+        }
+        NetworkResponse::DownloadFailed(tab_id, download_id, msg) => {
+            if !download_is_active(app, download_id) {
+                return Ok(());
+            }
+            if let Some(d) = app.downloads.get_mut(&download_id) {
+                d.status = DownloadStatus::Failed(msg.clone());
+                d.last_update = std::time::Instant::now();
+            }
+            sync_tab_download(app, tab_id, download_id, format!("Download failed: {}", msg));
+            return Ok(());
+        }
+        NetworkResponse::DownloadConflict(tab_id, download_id, path, reply) => {
+            if !download_is_active(app, download_id) {
+                return Ok(());
+            }
+            let url = app.downloads[&download_id].url.clone();
+            let Some(tab) = app.tabs.iter_mut().find(|t| t.id == tab_id) else {
+                // Nobody left to ask
+                app.cancel_download(download_id);
+                return Ok(());
+            };
+            let target_path = std::path::PathBuf::from(path);
+            tab.download_prompt = Some(DownloadPrompt {
+                url,
+                filename: target_path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().into_owned())
+                    .unwrap_or_default(),
+                target_path,
+                file_exists: true,
+                conflict: Some(DownloadConflict { download_id, reply }),
+            });
+            tab.status_message = String::from("File exists - Overwrite, Rename or Cancel?");
+            return Ok(());
+        }
+        other => other,
+    };
+
     let target_id = match &response {
         NetworkResponse::Success(id, ..) => *id,
         NetworkResponse::Error(id, ..) => *id,
         NetworkResponse::Loading(id) => *id,
         NetworkResponse::Info(id, ..) => *id,
+        NetworkResponse::LinkChecked(id, ..) => *id,
+        NetworkResponse::ResponseInfo(id, ..) => *id,
+        NetworkResponse::AuthRequired(id, ..) => *id,
+        NetworkResponse::PageTooLarge(id, ..) => *id,
+        NetworkResponse::JumpFailed(id, ..) => *id,
+        NetworkResponse::CertificateError(id, ..) => *id,
         NetworkResponse::DownloadProgress(id, ..) => *id,
         NetworkResponse::DownloadFinished(id, ..) => *id,
+        NetworkResponse::DownloadFailed(id, ..) => *id,
+        NetworkResponse::DownloadConflict(id, ..) => *id,
     };
 
     if let Some(index) = app.tabs.iter().position(|t| t.id == target_id) {
         match response {
-            NetworkResponse::DownloadProgress(_, downloaded, total) => {
-                let tab = &mut app.tabs[index];
-                tab.download_state = Some(crate::models::Download {
-                    _id: target_id,
-                    filename: String::from("Downloading..."),
-                    bytes_downloaded: downloaded,
-                    total_size: total,
-                    status: crate::models::DownloadStatus::Active,
-                });
-                // Update status message for footer
-                tab.status_message = match total {
-                    Some(t) => format!("Downloading: {}%", (downloaded * 100) / t),
-                    None => format!("Downloading: {} bytes", downloaded),
-                };
-            }
-            NetworkResponse::DownloadFinished(_, filename) => {
-                let tab = &mut app.tabs[index];
-                //tab.download_state = None; // Clear progress state
-                if let Some(ref mut d) = tab.download_state {
-                    d.status = crate::models::DownloadStatus::Completed;
-                    d.filename = filename.clone();
-                }
-                tab.status_message = format!("Download complete: {}", filename);
-            }
-            NetworkResponse::Success(_, title, html_source) => {
+            // Handled above
+            NetworkResponse::DownloadProgress(..)
+            | NetworkResponse::DownloadFinished(..)
+            | NetworkResponse::DownloadFailed(..)
+            | NetworkResponse::DownloadConflict(..) => {}
+            NetworkResponse::Success(_, final_url, title, html_source, elapsed) => {
+                log::info!("tab {}: loaded in {}ms", target_id, elapsed.as_millis());
                 let tab = &mut app.tabs[index];
+                tab.loading_since = None;
+                tab.pending_load = None;
                 tab.page_title = title;
                 tab.html_source = html_source;
                 tab.scroll = 0;
-                tab.status_message = String::from("Loaded");
+                tab.marks.clear();
+                // Show where redirects actually led; relative links resolve against it too
+                tab.url_input = final_url;
+                tab.loaded_url = tab.url_input.clone();
+                tab.certificate_host = None;
+                let metadata = crate::network::parse_html_metadata(&tab.html_source);
+                tab.base_url = metadata
+                    .base_href
+                    .map(|href| crate::network::resolve_url(&tab.url_input, &href));
+                tab.feed_url = metadata.feed_href.map(|href| tab.resolve_link(&href));
+                // The response's headers arrive ahead of the body
+                let content_type = tab.page_info.as_ref().and_then(|i| i.content_type.clone());
+                tab.table_delimiter =
+                    crate::network::table_delimiter(content_type.as_deref(), &tab.url_input);
+                tab.h_scroll = 0;
+                let restore_scroll = tab.pending_scroll.take();
+                // Typed values belong to the previous page
+                tab.forms.clear();
+                tab.focused_input = None;
+                if tab.input_mode == InputMode::FormInput {
+                    tab.input_mode = InputMode::Normal;
+                }
+                tab.status_message = format!("Loaded in {}ms", elapsed.as_millis());
                 app.render_tab(index, terminal_width);
+                let max_links = app.config.max_link_regions;
+                let feed_key = app.keymap.normal_key(Action::OpenFeed);
+                let tab = &mut app.tabs[index];
+                log::debug!(
+                    "tab {}: rendered {} lines, {} links",
+                    target_id,
+                    tab.rendered_content.len(),
+                    tab.link_regions.len()
+                );
+                // Back navigation lands where the page was left, as far as the new render allows
+                if let Some(scroll) = restore_scroll {
+                    tab.scroll = scroll.min(tab.rendered_content.len().saturating_sub(1));
+                }
+                if tab.link_regions.len() >= max_links {
+                    tab.status_message
+                        .push_str(&format!(" - only the first {} links are navigable", max_links));
+                }
+                if tab.feed_url.is_some() {
+                    tab.status_message.push_str(" - Feed available");
+                    if let Some(key) = feed_key {
+                        tab.status_message.push_str(&format!(" (press {} to open)", key));
+                    }
+                }
             }
             NetworkResponse::Error(_, msg) => {
+                log::warn!("tab {}: {}", target_id, msg);
                 let tab = &mut app.tabs[index];
-                if let Some(ref mut d) = tab.download_state {
-                    d.status = crate::models::DownloadStatus::Failed(msg.clone());
-                }
+                tab.loading_since = None;
+                tab.pending_load = None;
+                tab.loaded_url = tab.url_input.clone();
+                tab.certificate_host = None;
                 tab.page_title = String::from("Error");
-                tab.html_source = format!("<h1>Error</h1><hr><p style='color:red'>{}</p>", msg);
+                tab.html_source = crate::network::error_page_html(&tab.url_input, &msg);
                 tab.scroll = 0;
+                tab.marks.clear();
+                tab.table_delimiter = None;
+                tab.base_url = None;
+                tab.feed_url = None;
+                tab.pending_scroll = None;
+                // Land on the Retry link so Enter tries again
+                tab.selected_link_index = 0;
                 tab.status_message = String::from("Error");
                 app.render_tab(index, terminal_width);
             }
+            NetworkResponse::JumpFailed(_, url, domain, attempts) => {
+                log::warn!("tab {}: no jump service resolved {}", target_id, domain);
+                let tab = &mut app.tabs[index];
+                tab.loading_since = None;
+                tab.pending_load = None;
+                tab.loaded_url = url.clone();
+                tab.certificate_host = None;
+                tab.page_title = String::from("Address Not Found");
+                tab.html_source = crate::network::jump_failure_page_html(&url, &domain, &attempts);
+                tab.scroll = 0;
+                tab.marks.clear();
+                tab.table_delimiter = None;
+                tab.base_url = None;
+                tab.feed_url = None;
+                tab.pending_scroll = None;
+                // Land on the Retry link so Enter tries again
+                tab.selected_link_index = 0;
+                tab.status_message = format!("Couldn't resolve {}", domain);
+                app.render_tab(index, terminal_width);
+            }
+            NetworkResponse::CertificateError(_, url, problem) => {
+                log::warn!("tab {}: certificate refused for {}: {}", target_id, url, problem);
+                let retry_key = app.keymap.normal_key(Action::TrustCertificate);
+                let retry_key = retry_key.filter(|_| app.config.allow_insecure_retry);
+                let tab = &mut app.tabs[index];
+                tab.loading_since = None;
+                tab.pending_load = None;
+                tab.loaded_url = url.clone();
+                tab.certificate_host = crate::network::auth_host_key(&url);
+                tab.page_title = String::from("Security Warning");
+                tab.html_source = crate::network::certificate_error_page_html(
+                    &url,
+                    &problem,
+                    retry_key.as_deref(),
+                );
+                tab.scroll = 0;
+                tab.marks.clear();
+                tab.table_delimiter = None;
+                tab.base_url = None;
+                tab.feed_url = None;
+                tab.pending_scroll = None;
+                tab.selected_link_index = 0;
+                tab.status_message = format!("Certificate problem: {}", problem);
+                app.render_tab(index, terminal_width);
+            }
+            NetworkResponse::ResponseInfo(_, info) => {
+                log::info!("tab {}: {} {}", target_id, info.status, info.final_url);
+                app.tabs[index].page_info = Some(info);
+            }
+            NetworkResponse::PageTooLarge(_, url, limit) => {
+                log::warn!("tab {}: {} is larger than {} bytes", target_id, url, limit);
+                let download_key = app.keymap.normal_key(Action::Download);
+                let tab = &mut app.tabs[index];
+                tab.loading_since = None;
+                tab.pending_load = None;
+                tab.loaded_url = url.clone();
+                tab.certificate_host = None;
+                tab.page_title = String::from("Page Too Large");
+                tab.html_source =
+                    crate::network::oversize_page_html(&url, limit, download_key.as_deref());
+                tab.scroll = 0;
+                tab.marks.clear();
+                tab.table_delimiter = None;
+                tab.base_url = None;
+                tab.feed_url = None;
+                tab.forms.clear();
+                // Land on the link so `d` downloads the raw content
+                tab.selected_link_index = 0;
+                tab.status_message = String::from("Page exceeds size limit");
+                app.render_tab(index, terminal_width);
+            }
+            NetworkResponse::AuthRequired(_, url, realm) => {
+                log::info!("tab {}: {} requires a login", target_id, url);
+                let host = crate::network::auth_host_key(&url).unwrap_or_default();
+                // Whatever was sent didn't work, so stop offering it
+                app.credentials.remove(&host);
+                let tab = &mut app.tabs[index];
+                tab.page_title = String::from("Login Required");
+                tab.loading_since = None;
+                tab.pending_load = None;
+                tab.status_message = format!("{} requires a login", host);
+                tab.auth_prompt = Some(AuthPrompt {
+                    url,
+                    host,
+                    realm,
+                    username: String::new(),
+                    password: String::new(),
+                    editing_password: false,
+                });
+            }
             NetworkResponse::Loading(_) => {
                 let tab = &mut app.tabs[index];
+                // The filtered links belong to the page being left
+                tab.end_link_filter(false);
+                tab.loading_since = Some(std::time::Instant::now());
+                tab.page_info = None;
                 tab.page_title = String::from("Loading...");
-                tab.status_message = String::from("Fetching...");
+                tab.status_message = tab.loading_message();
             }
             NetworkResponse::Info(_, msg) => {
                 let tab = &mut app.tabs[index];
-                tab.status_message = msg;
+                // Progress queued before the load was cancelled or finished is out of date
+                if tab.loading_since.is_some() {
+                    tab.status_message = msg;
+                }
+            }
+            NetworkResponse::LinkChecked(_, url, is_download, snapshot) => {
+                // Only act if the user is still looking at the tab that asked; a tab left behind
+                // mustn't stay on "Checking link..." either
+                if index != app.active_tab_index {
+                    app.tabs[index].set_transient_status("Link not followed after switching tabs");
+                } else if app.tabs[index].has_moved_on(&snapshot) {
+                    // The navigation started since then wins over the link
+                    log::debug!("tab {}: link check for {} came too late", target_id, url);
+                } else {
+                    let is_download = is_download.unwrap_or_else(|| is_downloadable_file(&url));
+                    app.open_link_from_page(url, is_download);
+                }
             }
         }
     }
@@ -574,6 +1314,7 @@ pub fn handle_network_event<B: Backend>(
 }
 
 fn handle_search_mode(app: &mut App, key: KeyEvent) -> Result<bool> {
+    let paste = if is_paste_key(&key) { clipboard_paste(app) } else { None };
     let tab = app.current_tab();
     match key.code {
         KeyCode::Esc => {
@@ -585,7 +1326,11 @@ fn handle_search_mode(app: &mut App, key: KeyEvent) -> Result<bool> {
         }
         KeyCode::Char(c) => {
             if let Some(search_state) = &mut tab.search_state {
-                search_state.query.push(c);
+                match paste {
+                    Some(text) => search_state.query.push_str(&text),
+                    None if is_paste_key(&key) => return Ok(false),
+                    None => search_state.query.push(c),
+                }
                 // Clone the query to avoid borrowing issues
                 let query = search_state.query.clone();
                 let _ = search_state; // Release the borrow
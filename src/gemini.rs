@@ -0,0 +1,406 @@
+use crate::constants::{BROWSING_TIMEOUT_SECS, GEMINI_DEFAULT_PORT, MAX_REDIRECTS};
+use crate::models::PageInfo;
+use crate::network::{escape_html, parse_html_metadata, resolve_allowed, target_violation};
+
+use ring::digest;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsConnector;
+use tokio_rustls::rustls::client::danger::{
+    HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier,
+};
+use tokio_rustls::rustls::crypto::{
+    WebPkiSupportedAlgorithms, ring as provider, verify_tls12_signature, verify_tls13_signature,
+};
+use tokio_rustls::rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use tokio_rustls::rustls::{self, ClientConfig, DigitallySignedStruct, SignatureScheme};
+use url::Url;
+
+/// A parsed Gemini response: two-digit status, the meta line and the body bytes
+pub struct GeminiResponse {
+    pub status: u8,
+    pub meta: String,
+    pub body: Vec<u8>,
+}
+
+/// Trust-on-first-use store of certificate fingerprints, one `host:port fingerprint` per line.
+/// The first certificate seen for a host is pinned; a different one later is rejected.
+pub struct KnownHosts {
+    path: Option<PathBuf>,
+    hosts: Mutex<HashMap<String, String>>,
+}
+
+impl KnownHosts {
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let hosts = path
+            .as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .map(|text| {
+                text.lines()
+                    .filter_map(|line| line.split_once(' '))
+                    .map(|(host, fp)| (host.to_string(), fp.trim().to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            path,
+            hosts: Mutex::new(hosts),
+        }
+    }
+
+    pub fn fingerprint(&self, host: &str) -> Option<String> {
+        self.hosts.lock().ok()?.get(host).cloned()
+    }
+
+    /// Accept `fingerprint` for `host` if it matches the pinned one, pinning it on first contact
+    pub fn check_or_trust(&self, host: &str, fingerprint: &str) -> Result<(), String> {
+        let mut hosts = self
+            .hosts
+            .lock()
+            .map_err(|_| "Known hosts store is poisoned")?;
+        match hosts.get(host) {
+            Some(pinned) if pinned == fingerprint => Ok(()),
+            Some(_) => Err(format!(
+                "Certificate for {} changed since it was first trusted",
+                host
+            )),
+            None => {
+                hosts.insert(host.to_string(), fingerprint.to_string());
+                self.save(&hosts);
+                Ok(())
+            }
+        }
+    }
+
+    fn save(&self, hosts: &HashMap<String, String>) {
+        let Some(path) = &self.path else {
+            return;
+        };
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        let mut lines: Vec<String> = hosts
+            .iter()
+            .map(|(h, fp)| format!("{} {}", h, fp))
+            .collect();
+        lines.sort();
+        let _ = std::fs::write(path, lines.join("\n") + "\n");
+    }
+}
+
+impl std::fmt::Debug for KnownHosts {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KnownHosts")
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+/// Gemini capsules are mostly self-signed, so identity comes from TOFU pinning instead of CAs.
+/// Handshake signatures are still verified against the presented certificate.
+#[derive(Debug)]
+struct TofuVerifier {
+    host: String,
+    known_hosts: Arc<KnownHosts>,
+    algorithms: WebPkiSupportedAlgorithms,
+}
+
+impl ServerCertVerifier for TofuVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let fingerprint = certificate_fingerprint(end_entity);
+        self.known_hosts
+            .check_or_trust(&self.host, &fingerprint)
+            .map(|_| ServerCertVerified::assertion())
+            .map_err(rustls::Error::General)
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.algorithms.supported_schemes()
+    }
+}
+
+/// Hex SHA-256 of a DER certificate
+pub fn certificate_fingerprint(cert: &[u8]) -> String {
+    digest::digest(&digest::SHA256, cert)
+        .as_ref()
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+pub fn is_gemini_url(url: &str) -> bool {
+    url.trim().to_lowercase().starts_with("gemini://")
+}
+
+/// Called with a stage (`Connecting to`, `Downloading`) and the host it concerns as each
+/// request gets that far
+pub type PhaseReporter<'a> = &'a (dyn Fn(&str, &str) + Send + Sync);
+
+/// Fetch a page, following up to `MAX_REDIRECTS` redirects.
+/// Returns the response info, page title and HTML ready for the renderer.
+/// Unless `allow_local_targets`, neither the page nor a redirect may reach a local address.
+pub async fn fetch_page(
+    url: &str,
+    known_hosts: Arc<KnownHosts>,
+    max_size: u64,
+    allow_local_targets: bool,
+    on_phase: PhaseReporter<'_>,
+) -> Result<(PageInfo, String, String), String> {
+    let mut url = Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+    for _ in 0..=MAX_REDIRECTS {
+        if !allow_local_targets && let Some(reason) = target_violation(&url) {
+            return Err(reason);
+        }
+        let response = fetch(
+            &url,
+            Arc::clone(&known_hosts),
+            max_size,
+            allow_local_targets,
+            on_phase,
+        )
+        .await?;
+        match response.status / 10 {
+            2 => {
+                let mime = if response.meta.is_empty() {
+                    String::from("text/gemini")
+                } else {
+                    response.meta.clone()
+                };
+                let info = PageInfo {
+                    final_url: url.to_string(),
+                    status: response.status as u16,
+                    content_type: Some(mime.clone()),
+                    content_length: Some(response.body.len() as u64),
+                    server: None,
+                };
+                let text = String::from_utf8_lossy(&response.body);
+                let html = if mime.starts_with("text/gemini") {
+                    gemtext_to_html(&text)
+                } else if mime.starts_with("text/") {
+                    format!(
+                        "<html><body><pre>{}</pre></body></html>",
+                        escape_html(&text)
+                    )
+                } else {
+                    return Err(format!("Unsupported Gemini content type: {}", mime));
+                };
+                let title = parse_html_metadata(&html).title;
+                return Ok((info, title, html));
+            }
+            3 => {
+                url = url
+                    .join(&response.meta)
+                    .map_err(|e| format!("Invalid redirect: {}", e))?;
+                if url.scheme() != "gemini" {
+                    return Err(format!("Refusing cross-protocol redirect to {}", url));
+                }
+            }
+            1 => {
+                return Err(format!(
+                    "Input required, which isn't supported: {}",
+                    response.meta
+                ));
+            }
+            6 => return Err(format!("Client certificate required: {}", response.meta)),
+            _ => {
+                return Err(format!(
+                    "Gemini error {}: {}",
+                    response.status, response.meta
+                ));
+            }
+        }
+    }
+    Err(String::from("Too many redirects"))
+}
+
+/// Send a single Gemini request and read the whole response, up to `max_size` bytes.
+/// The host must resolve to a public address unless `allow_local_targets`.
+pub async fn fetch(
+    url: &Url,
+    known_hosts: Arc<KnownHosts>,
+    max_size: u64,
+    allow_local_targets: bool,
+    on_phase: PhaseReporter<'_>,
+) -> Result<GeminiResponse, String> {
+    let host = url.host_str().ok_or("Gemini URL has no host")?.to_string();
+    let port = url.port().unwrap_or(GEMINI_DEFAULT_PORT);
+
+    let crypto = Arc::new(provider::default_provider());
+    let verifier = TofuVerifier {
+        host: format!("{}:{}", host, port),
+        known_hosts,
+        algorithms: crypto.signature_verification_algorithms,
+    };
+    let config = ClientConfig::builder_with_provider(crypto)
+        .with_safe_default_protocol_versions()
+        .map_err(|e| format!("TLS error: {}", e))?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth();
+    let connector = TlsConnector::from(Arc::new(config));
+
+    // IPv6 literals come back bracketed from the URL
+    let bare_host = host
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .to_string();
+    let server_name =
+        ServerName::try_from(bare_host.clone()).map_err(|e| format!("Invalid host: {}", e))?;
+
+    let exchange = async {
+        // The name is looked up and checked while connecting, so there's no separate resolving
+        // phase. The connection goes to the checked addresses, not to a second lookup.
+        on_phase("Connecting to", &host);
+        let addrs = resolve_allowed(&bare_host, port, allow_local_targets)
+            .await
+            .map_err(|e| format!("Connection failed: {}", e))?;
+        let tcp = TcpStream::connect(addrs.as_slice())
+            .await
+            .map_err(|e| format!("Connection failed: {}", e))?;
+        let mut tls = connector
+            .connect(server_name, tcp)
+            .await
+            .map_err(|e| format!("TLS error: {}", e))?;
+        tls.write_all(format!("{}\r\n", url).as_bytes())
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?;
+        on_phase("Downloading", &host);
+
+        let mut raw = Vec::new();
+        let read = (&mut tls).take(max_size + 1).read_to_end(&mut raw).await;
+        match read {
+            Ok(_) => {}
+            // Plenty of servers hang up without a TLS close_notify
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof && !raw.is_empty() => {}
+            Err(e) => return Err(format!("Request failed: {}", e)),
+        }
+        if raw.len() as u64 > max_size {
+            return Err(String::from("Page too large"));
+        }
+        parse_response(&raw)
+    };
+
+    tokio::time::timeout(Duration::from_secs(BROWSING_TIMEOUT_SECS), exchange)
+        .await
+        .map_err(|_| String::from("Timeout: Gemini server didn't answer"))?
+}
+
+/// Split a raw response into its `<status> <meta>\r\n` header and body
+pub fn parse_response(raw: &[u8]) -> Result<GeminiResponse, String> {
+    let header_end = raw
+        .windows(2)
+        .position(|w| w == b"\r\n")
+        .ok_or("Malformed Gemini response header")?;
+    let header = String::from_utf8_lossy(&raw[..header_end]);
+    let (code, meta) = header.split_once(' ').unwrap_or((&header, ""));
+    let status = code
+        .parse::<u8>()
+        .ok()
+        .filter(|s| (10..=69).contains(s))
+        .ok_or_else(|| format!("Malformed Gemini status: {}", code))?;
+    Ok(GeminiResponse {
+        status,
+        meta: meta.trim().to_string(),
+        body: raw[header_end + 2..].to_vec(),
+    })
+}
+
+/// Convert gemtext to HTML for `DomRenderer`, so link lines become ordinary link regions
+pub fn gemtext_to_html(text: &str) -> String {
+    let mut html = String::from("<html><body>");
+    let mut in_pre = false;
+    let mut in_list = false;
+
+    for line in text.lines() {
+        if line.starts_with("```") {
+            if in_list {
+                html.push_str("</ul>");
+                in_list = false;
+            }
+            html.push_str(if in_pre { "</pre>" } else { "<pre>" });
+            in_pre = !in_pre;
+            continue;
+        }
+        if in_pre {
+            html.push_str(&escape_html(line));
+            html.push('\n');
+            continue;
+        }
+
+        if let Some(item) = line.strip_prefix("* ") {
+            if !in_list {
+                html.push_str("<ul>");
+                in_list = true;
+            }
+            html.push_str(&format!("<li>{}</li>", escape_html(item.trim())));
+            continue;
+        }
+        if in_list {
+            html.push_str("</ul>");
+            in_list = false;
+        }
+
+        if let Some(link) = line.strip_prefix("=>") {
+            let link = link.trim();
+            let (target, label) = match link.split_once(char::is_whitespace) {
+                Some((target, label)) if !label.trim().is_empty() => (target, label.trim()),
+                _ => (link, link),
+            };
+            if !target.is_empty() {
+                html.push_str(&format!(
+                    "<p><a href=\"{}\">{}</a></p>",
+                    escape_html(target),
+                    escape_html(label)
+                ));
+            }
+        } else if let Some(heading) = line.strip_prefix("###") {
+            html.push_str(&format!("<h3>{}</h3>", escape_html(heading.trim())));
+        } else if let Some(heading) = line.strip_prefix("##") {
+            html.push_str(&format!("<h2>{}</h2>", escape_html(heading.trim())));
+        } else if let Some(heading) = line.strip_prefix('#') {
+            html.push_str(&format!("<h1>{}</h1>", escape_html(heading.trim())));
+        } else if let Some(quote) = line.strip_prefix('>') {
+            html.push_str(&format!("<p><i>{}</i></p>", escape_html(quote.trim())));
+        } else if !line.trim().is_empty() {
+            html.push_str(&format!("<p>{}</p>", escape_html(line)));
+        }
+    }
+
+    if in_list {
+        html.push_str("</ul>");
+    }
+    if in_pre {
+        html.push_str("</pre>");
+    }
+    html.push_str("</body></html>");
+    html
+}
@@ -0,0 +1,486 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+use serde::de::{Deserializer, MapAccess, Visitor};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+/// Commands available from normal mode
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Action {
+    VisualMode,
+    Download,
+    Dismiss,
+    CopyLink,
+    CopyPageUrl,
+    PageInfo,
+    DownloadManager,
+    CycleUserAgent,
+    FocusInput,
+    CancelDownload,
+    NewTab,
+    OpenInNewTab,
+    OpenInBackgroundTab,
+    CloseTab,
+    CloseOtherTabs,
+    CloseTabsToRight,
+    NextTab,
+    PreviousTab,
+    Quit,
+    EditUrl,
+    Search,
+    FilterLinks,
+    NextMatch,
+    PreviousMatch,
+    ScrollDown,
+    ScrollUp,
+    ScrollLeft,
+    ScrollRight,
+    ToggleSource,
+    ToggleWrap,
+    NarrowColumn,
+    WidenColumn,
+    ResetColumnWidth,
+    ReaderMode,
+    CursorLeft,
+    CursorRight,
+    CursorUp,
+    CursorDown,
+    Back,
+    ParentDirectory,
+    OpenFeed,
+    NextLink,
+    PreviousLink,
+    FollowLink,
+    ToggleI2p,
+    SetMark,
+    JumpToMark,
+    TrustCertificate,
+    ToggleSplit,
+    SwitchPane,
+}
+
+/// Commands available while typing in the address bar
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EditAction {
+    Submit,
+    Cancel,
+    CopyAddress,
+    ClearLine,
+    DeleteWord,
+    DeleteChar,
+    LineStart,
+    LineEnd,
+    CursorLeft,
+    CursorRight,
+    Paste,
+    ClearAndPaste,
+}
+
+// Names used in keymap.json
+const ACTION_NAMES: &[(&str, Action)] = &[
+    ("visual_mode", Action::VisualMode),
+    ("download", Action::Download),
+    ("dismiss", Action::Dismiss),
+    ("copy_link", Action::CopyLink),
+    ("copy_page_url", Action::CopyPageUrl),
+    ("page_info", Action::PageInfo),
+    ("download_manager", Action::DownloadManager),
+    ("cycle_user_agent", Action::CycleUserAgent),
+    ("focus_input", Action::FocusInput),
+    ("cancel_download", Action::CancelDownload),
+    ("new_tab", Action::NewTab),
+    ("open_in_new_tab", Action::OpenInNewTab),
+    ("open_in_background_tab", Action::OpenInBackgroundTab),
+    ("close_tab", Action::CloseTab),
+    ("close_other_tabs", Action::CloseOtherTabs),
+    ("close_tabs_to_right", Action::CloseTabsToRight),
+    ("next_tab", Action::NextTab),
+    ("previous_tab", Action::PreviousTab),
+    ("quit", Action::Quit),
+    ("edit_url", Action::EditUrl),
+    ("search", Action::Search),
+    ("filter_links", Action::FilterLinks),
+    ("next_match", Action::NextMatch),
+    ("previous_match", Action::PreviousMatch),
+    ("scroll_down", Action::ScrollDown),
+    ("scroll_up", Action::ScrollUp),
+    ("scroll_left", Action::ScrollLeft),
+    ("scroll_right", Action::ScrollRight),
+    ("toggle_source", Action::ToggleSource),
+    ("toggle_wrap", Action::ToggleWrap),
+    ("narrow_column", Action::NarrowColumn),
+    ("widen_column", Action::WidenColumn),
+    ("reset_column_width", Action::ResetColumnWidth),
+    ("reader_mode", Action::ReaderMode),
+    ("cursor_left", Action::CursorLeft),
+    ("cursor_right", Action::CursorRight),
+    ("cursor_up", Action::CursorUp),
+    ("cursor_down", Action::CursorDown),
+    ("back", Action::Back),
+    ("parent_directory", Action::ParentDirectory),
+    ("open_feed", Action::OpenFeed),
+    ("next_link", Action::NextLink),
+    ("previous_link", Action::PreviousLink),
+    ("follow_link", Action::FollowLink),
+    ("toggle_i2p", Action::ToggleI2p),
+    ("set_mark", Action::SetMark),
+    ("jump_to_mark", Action::JumpToMark),
+    ("trust_certificate", Action::TrustCertificate),
+    ("toggle_split", Action::ToggleSplit),
+    ("switch_pane", Action::SwitchPane),
+];
+
+const EDIT_ACTION_NAMES: &[(&str, EditAction)] = &[
+    ("submit", EditAction::Submit),
+    ("cancel", EditAction::Cancel),
+    ("copy_address", EditAction::CopyAddress),
+    ("clear_line", EditAction::ClearLine),
+    ("delete_word", EditAction::DeleteWord),
+    ("delete_char", EditAction::DeleteChar),
+    ("line_start", EditAction::LineStart),
+    ("line_end", EditAction::LineEnd),
+    ("cursor_left", EditAction::CursorLeft),
+    ("cursor_right", EditAction::CursorRight),
+    ("paste", EditAction::Paste),
+    ("clear_and_paste", EditAction::ClearAndPaste),
+];
+
+const DEFAULT_NORMAL_BINDINGS: &[(&str, Action)] = &[
+    ("v", Action::VisualMode),
+    ("d", Action::Download),
+    ("esc", Action::Dismiss),
+    ("c", Action::CopyLink),
+    ("y", Action::CopyPageUrl),
+    ("i", Action::PageInfo),
+    ("J", Action::DownloadManager),
+    ("U", Action::CycleUserAgent),
+    ("f", Action::FocusInput),
+    ("x", Action::CancelDownload),
+    ("n", Action::NewTab),
+    ("t", Action::OpenInNewTab),
+    ("ctrl+t", Action::OpenInBackgroundTab),
+    ("w", Action::CloseTab),
+    ("O", Action::CloseOtherTabs),
+    ("}", Action::CloseTabsToRight),
+    ("]", Action::NextTab),
+    ("[", Action::PreviousTab),
+    ("q", Action::Quit),
+    ("e", Action::EditUrl),
+    ("/", Action::Search),
+    (";", Action::FilterLinks),
+    (">", Action::NextMatch),
+    ("<", Action::PreviousMatch),
+    ("down", Action::ScrollDown),
+    ("up", Action::ScrollUp),
+    ("H", Action::ScrollLeft),
+    ("shift+left", Action::ScrollLeft),
+    ("L", Action::ScrollRight),
+    ("shift+right", Action::ScrollRight),
+    ("V", Action::ToggleSource),
+    ("W", Action::ToggleWrap),
+    ("-", Action::NarrowColumn),
+    ("+", Action::WidenColumn),
+    ("=", Action::ResetColumnWidth),
+    ("R", Action::ReaderMode),
+    ("h", Action::CursorLeft),
+    ("l", Action::CursorRight),
+    ("k", Action::CursorUp),
+    ("j", Action::CursorDown),
+    ("backspace", Action::Back),
+    ("left", Action::Back),
+    ("u", Action::ParentDirectory),
+    ("F", Action::OpenFeed),
+    ("tab", Action::NextLink),
+    ("backtab", Action::PreviousLink),
+    ("enter", Action::FollowLink),
+    ("p", Action::ToggleI2p),
+    ("m", Action::SetMark),
+    ("`", Action::JumpToMark),
+    ("!", Action::TrustCertificate),
+    ("s", Action::ToggleSplit),
+    ("ctrl+w", Action::SwitchPane),
+];
+
+const DEFAULT_EDIT_BINDINGS: &[(&str, EditAction)] = &[
+    ("enter", EditAction::Submit),
+    ("esc", EditAction::Cancel),
+    ("ctrl+y", EditAction::CopyAddress),
+    ("ctrl+u", EditAction::ClearLine),
+    ("ctrl+w", EditAction::DeleteWord),
+    ("alt+backspace", EditAction::DeleteWord),
+    ("backspace", EditAction::DeleteChar),
+    ("ctrl+a", EditAction::LineStart),
+    ("home", EditAction::LineStart),
+    ("ctrl+e", EditAction::LineEnd),
+    ("end", EditAction::LineEnd),
+    ("left", EditAction::CursorLeft),
+    ("right", EditAction::CursorRight),
+    ("ctrl+v", EditAction::Paste),
+    ("ctrl+k", EditAction::ClearAndPaste),
+];
+
+/// A key together with the modifiers that matter for binding it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct KeyBinding {
+    pub code: KeyCode,
+    pub modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    /// Shift is folded into the character itself (`J`, `}`), so it only counts for other keys
+    fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        let mut modifiers =
+            modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::Char(c.to_ascii_uppercase())
+            }
+            KeyCode::BackTab => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                code
+            }
+            _ => code,
+        };
+        Self { code, modifiers }
+    }
+
+    pub fn from_event(key: &KeyEvent) -> Self {
+        Self::new(key.code, key.modifiers)
+    }
+
+    /// Parse `j`, `ctrl+t`, `shift+left`, `esc` and the like; None when it isn't a key
+    pub fn parse(text: &str) -> Option<Self> {
+        // A lone `+`, or one at the end of `ctrl++`, is the key itself
+        let (prefix, key) = match text.strip_suffix("++") {
+            Some(prefix) => (Some(prefix), "+"),
+            None if text == "+" => (None, "+"),
+            None => match text.rsplit_once('+') {
+                Some((prefix, key)) => (Some(prefix), key),
+                None => (None, text),
+            },
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for name in prefix.into_iter().flat_map(|prefix| prefix.split('+')) {
+            modifiers |= match name.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return None,
+            };
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_ascii_lowercase().as_str() {
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "space" => KeyCode::Char(' '),
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                _ => return None,
+            },
+        };
+        Some(Self::new(code, modifiers))
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "ctrl+"),
+            (KeyModifiers::ALT, "alt+"),
+            (KeyModifiers::SHIFT, "shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Enter => f.write_str("enter"),
+            KeyCode::Esc => f.write_str("esc"),
+            KeyCode::Tab => f.write_str("tab"),
+            KeyCode::BackTab => f.write_str("backtab"),
+            KeyCode::Backspace => f.write_str("backspace"),
+            KeyCode::Delete => f.write_str("delete"),
+            KeyCode::Left => f.write_str("left"),
+            KeyCode::Right => f.write_str("right"),
+            KeyCode::Up => f.write_str("up"),
+            KeyCode::Down => f.write_str("down"),
+            KeyCode::Home => f.write_str("home"),
+            KeyCode::End => f.write_str("end"),
+            KeyCode::PageUp => f.write_str("pageup"),
+            KeyCode::PageDown => f.write_str("pagedown"),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+/// Resolves key presses to actions; `Keymap::default()` is the built-in layout.
+/// `keymap.json` has a `normal` and an `editing` section mapping keys such as `h`, `ctrl+t`
+/// or `shift+left` to action names, on top of the defaults.
+#[derive(Clone, Debug)]
+pub struct Keymap {
+    normal: HashMap<KeyBinding, Action>,
+    editing: HashMap<KeyBinding, EditAction>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        Self {
+            normal: default_bindings(DEFAULT_NORMAL_BINDINGS),
+            editing: default_bindings(DEFAULT_EDIT_BINDINGS),
+        }
+    }
+}
+
+fn default_bindings<A: Copy>(table: &[(&str, A)]) -> HashMap<KeyBinding, A> {
+    table
+        .iter()
+        .map(|&(key, action)| {
+            (
+                KeyBinding::parse(key).expect("valid default binding"),
+                action,
+            )
+        })
+        .collect()
+}
+
+/// Section entries in file order, keeping duplicates so conflicts can be reported
+#[derive(Default)]
+struct Entries(Vec<(String, String)>);
+
+impl<'de> Deserialize<'de> for Entries {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct EntriesVisitor;
+        impl<'de> Visitor<'de> for EntriesVisitor {
+            type Value = Entries;
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map of keys to action names")
+            }
+            fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<Entries, M::Error> {
+                let mut entries = Vec::new();
+                while let Some(entry) = map.next_entry()? {
+                    entries.push(entry);
+                }
+                Ok(Entries(entries))
+            }
+        }
+        deserializer.deserialize_map(EntriesVisitor)
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct KeymapFile {
+    normal: Entries,
+    editing: Entries,
+}
+
+impl Keymap {
+    /// Load the keymap file on top of the defaults. A missing file is not a problem;
+    /// anything else wrong with it is returned alongside the keymap.
+    pub fn load(path: &Path) -> (Self, Vec<String>) {
+        match std::fs::read_to_string(path) {
+            Ok(text) => Self::from_json(&text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (Self::default(), Vec::new()),
+            Err(e) => (
+                Self::default(),
+                vec![format!("can't read {}: {}", path.display(), e)],
+            ),
+        }
+    }
+
+    /// Apply the bindings in `text`, skipping (and describing) invalid or conflicting ones
+    pub fn from_json(text: &str) -> (Self, Vec<String>) {
+        let mut keymap = Self::default();
+        let file: KeymapFile = match serde_json::from_str(text) {
+            Ok(file) => file,
+            Err(e) => return (keymap, vec![e.to_string()]),
+        };
+        let mut problems = Vec::new();
+        apply(
+            &mut keymap.normal,
+            &file.normal,
+            ACTION_NAMES,
+            "normal",
+            &mut problems,
+        );
+        apply(
+            &mut keymap.editing,
+            &file.editing,
+            EDIT_ACTION_NAMES,
+            "editing",
+            &mut problems,
+        );
+        (keymap, problems)
+    }
+
+    pub fn normal_action(&self, key: &KeyEvent) -> Option<Action> {
+        self.normal.get(&KeyBinding::from_event(key)).copied()
+    }
+
+    /// A key that triggers `action` in normal mode, for hints; the shortest when there are several
+    pub fn normal_key(&self, action: Action) -> Option<String> {
+        self.normal
+            .iter()
+            .filter(|&(_, bound)| *bound == action)
+            .map(|(key, _)| key.to_string())
+            .min_by_key(|name| (name.len(), name.clone()))
+    }
+
+    pub fn editing_action(&self, key: &KeyEvent) -> Option<EditAction> {
+        self.editing.get(&KeyBinding::from_event(key)).copied()
+    }
+}
+
+fn apply<A: Copy + PartialEq>(
+    bindings: &mut HashMap<KeyBinding, A>,
+    entries: &Entries,
+    names: &[(&str, A)],
+    section: &str,
+    problems: &mut Vec<String>,
+) {
+    let mut bound: HashMap<KeyBinding, (&str, A)> = HashMap::new();
+    for (key, name) in &entries.0 {
+        let Some(binding) = KeyBinding::parse(key) else {
+            problems.push(format!("{}: '{}' is not a key", section, key));
+            continue;
+        };
+        let Some(&(_, action)) = names.iter().find(|(n, _)| n == name) else {
+            problems.push(format!("{}: unknown action '{}'", section, name));
+            continue;
+        };
+        // Digits start a count prefix in normal mode and are typed as-is in the address bar
+        if binding.modifiers.is_empty() && matches!(binding.code, KeyCode::Char('0'..='9')) {
+            problems.push(format!("{}: '{}' can't be rebound", section, key));
+            continue;
+        }
+        if let Some(&(first, other)) = bound.get(&binding)
+            && other != action
+        {
+            problems.push(format!(
+                "{}: '{}' is bound to both '{}' and '{}'",
+                section, key, first, name
+            ));
+            continue;
+        }
+        bound.insert(binding, (name, action));
+        bindings.insert(binding, action);
+    }
+}
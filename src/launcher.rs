@@ -0,0 +1,82 @@
+use crate::ui::{enter_tui, leave_tui};
+use std::io::{self, Write};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Runs a prepared command; swapped out in tests so nothing is actually launched
+pub type Launcher = Box<dyn FnMut(Command) -> io::Result<()> + Send>;
+
+/// Runs a command with the given text on its stdin and waits for it to exit; swapped out in tests
+pub type PipeRunner = Box<dyn FnMut(Command, String) -> io::Result<()> + Send>;
+
+/// Build the command that opens `path` with the OS default application
+pub fn open_command(path: &Path) -> Command {
+    let mut cmd = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(target_os = "windows") {
+        // `start` is a cmd builtin; the empty string is the window title
+        let mut cmd = Command::new("cmd");
+        cmd.args(["/C", "start", ""]);
+        cmd
+    } else {
+        Command::new("xdg-open")
+    };
+    cmd.arg(path);
+    cmd
+}
+
+/// Spawn the command detached from the TUI so its output can't corrupt the screen. A thread
+/// waits on it so it doesn't linger as a zombie until the browser exits.
+pub fn system_launcher() -> Launcher {
+    Box::new(|mut cmd: Command| {
+        let mut child = cmd
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        std::thread::spawn(move || {
+            let _ = child.wait();
+        });
+        Ok(())
+    })
+}
+
+/// Run a `:pipe` command line through the shell so its own pipes and redirects work
+pub fn shell_command(command_line: &str) -> Command {
+    let mut cmd = if cfg!(target_os = "windows") {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C");
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c");
+        cmd
+    };
+    cmd.arg(command_line);
+    cmd
+}
+
+/// Give the terminal to the command while it runs, since it may be interactive (e.g. `less`)
+pub fn system_pipe_runner() -> PipeRunner {
+    Box::new(|mut cmd: Command, input: String| {
+        let mut stdout = io::stdout();
+        leave_tui(&mut stdout)?;
+        let result = run_with_stdin(&mut cmd, &input);
+        enter_tui(&mut stdout)?;
+        result
+    })
+}
+
+fn run_with_stdin(cmd: &mut Command, input: &str) -> io::Result<()> {
+    let mut child = cmd.stdin(Stdio::piped()).spawn()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // Commands that stop reading early (e.g. `head`) close the pipe, which isn't an error
+        let _ = stdin.write_all(input.as_bytes());
+    }
+    let status = child.wait()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("exited with {}", status)))
+    }
+}
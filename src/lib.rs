@@ -1,9 +1,19 @@
 #![deny(unsafe_code)]
 
+pub mod about;
 pub mod app;
+pub mod cli;
+pub mod config;
 pub mod constants;
+pub mod control;
+pub mod dump;
 pub mod event_handler;
+pub mod gemini;
+pub mod keymap;
+pub mod launcher;
+pub mod logging;
 pub mod models;
 pub mod network;
 pub mod renderer;
+pub mod session;
 pub mod ui;
@@ -1,14 +1,23 @@
-use rynx_browser::app::App;
+use rynx_browser::app::{App, ResizeDebouncer};
+use rynx_browser::cli::{CliAction, USAGE, parse_args};
+use rynx_browser::config::Config;
+use rynx_browser::control::{self, ControlRequest};
+use rynx_browser::dump::dump_page;
 use rynx_browser::event_handler::{handle_key_event, handle_mouse_event, handle_network_event};
-use rynx_browser::ui::ui;
-
-use std::{error::Error, io, time::Duration};
+use rynx_browser::session::Session;
+use rynx_browser::ui::{
+    enter_tui, leave_tui, pop_terminal_title, push_terminal_title, ui, write_terminal_title,
+};
 
-use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event},
-    execute,
-    terminal::{EnterAlternateScreen, LeaveAlternateScreen, disable_raw_mode, enable_raw_mode},
+use std::{
+    error::Error,
+    io::{self, Write},
+    path::Path,
+    time::{Duration, Instant},
 };
+use tokio::sync::mpsc::Receiver;
+
+use crossterm::event::{self, Event};
 
 use ratatui::{
     Terminal,
@@ -18,72 +27,197 @@ use ratatui::{
 // MAIN LOOP (ASYNC)
 #[tokio::main] // This macro turns main() into an async runtime
 async fn main() -> Result<(), Box<dyn Error + Send + Sync>> {
+    // Bad arguments are reported before the terminal is taken over, where they can be read
+    let startup_urls = match parse_args(std::env::args().skip(1)) {
+        Ok(CliAction::Run(urls)) => urls,
+        Ok(CliAction::Dump { url, width }) => std::process::exit(run_dump(&url, width).await),
+        Ok(CliAction::Help) => {
+            println!("{}", USAGE);
+            return Ok(());
+        }
+        Err(message) => {
+            eprintln!("rynx: {}\n\n{}", message, USAGE);
+            std::process::exit(2);
+        }
+    };
+
     // This hook catches panics and restores the terminal before printing the error
     let original_hook = std::panic::take_hook();
     std::panic::set_hook(Box::new(move |panic_info| {
-        let _ = disable_raw_mode();
-        let _ = execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture);
+        let _ = leave_tui(&mut io::stdout());
+        let _ = pop_terminal_title(&mut io::stdout());
         original_hook(panic_info);
     }));
     // Setup Terminal
-    enable_raw_mode()?;
     let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+    enter_tui(&mut stdout)?;
+    push_terminal_title(&mut stdout)?;
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
     // Setup Channel
     let (tx, rx) = tokio::sync::mpsc::channel(rynx_browser::constants::CHANNEL_CAPACITY);
-    let app = App::new(tx, rx)?;
+    let config = Config::load();
+    if let Some(path) = config.log_file() {
+        // A log that can't be opened isn't worth refusing to start over
+        let _ = rynx_browser::logging::init(&path);
+    }
+    let control_path = config.control_socket();
+    // Nothing is read or written unless the user opted in to sessions. A run started with URLs
+    // isn't a session run, so it leaves the saved tabs alone.
+    let session_path = config
+        .session_file()
+        .filter(|_| config.restore_session && startup_urls.is_empty());
+    let mut app = App::with_config(tx, rx, config)?;
+    match session_path.as_deref().and_then(Session::load) {
+        Some(session) => app.restore_session(session),
+        None => app.open_startup_urls(startup_urls),
+    }
+    let control_requests = control_path.as_deref().and_then(start_control_socket);
 
     // Initialize MCP
     //app.init_mcp().await;
 
     // Run App
-    let res = run_app(&mut terminal, app).await;
+    let control_started = control_requests.is_some();
+    let res = run_app(&mut terminal, &mut app, control_requests).await;
 
     // Teardown
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
+    leave_tui(terminal.backend_mut())?;
+    pop_terminal_title(terminal.backend_mut())?;
     terminal.show_cursor()?;
 
     if let Err(err) = res {
         println!("{:?}", err)
     }
 
+    // Saved even after an error so the tabs aren't lost; a failed write only costs the session
+    if let Some(path) = session_path {
+        let _ = app.session().save(&path);
+    }
+
+    // Only a socket this run created is cleaned up
+    if let Some(path) = control_path.filter(|_| control_started) {
+        let _ = std::fs::remove_file(path);
+    }
+
     Ok(())
 }
 
-async fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> io::Result<()> {
+/// `--dump`: print the page to stdout without taking over the terminal; returns the exit code
+async fn run_dump(url: &str, width: u16) -> i32 {
+    let (tx, rx) = tokio::sync::mpsc::channel(rynx_browser::constants::CHANNEL_CAPACITY);
+    let config = Config::load();
+    if let Some(path) = config.log_file() {
+        let _ = rynx_browser::logging::init(&path);
+    }
+    let app = match App::with_config(tx, rx, config) {
+        Ok(app) => app,
+        Err(e) => {
+            eprintln!("rynx: {}", e);
+            return 1;
+        }
+    };
+    match dump_page::<CrosstermBackend<io::Stdout>>(app, url, width).await {
+        Ok(text) => {
+            // A reader that stopped early (`| head`) isn't an error
+            let _ = writeln!(io::stdout(), "{}", text);
+            0
+        }
+        Err(message) => {
+            eprintln!("rynx: {}: {}", url, message);
+            1
+        }
+    }
+}
+
+/// Listen for scripted commands; a socket that can't be set up is logged rather than fatal
+fn start_control_socket(path: &Path) -> Option<Receiver<ControlRequest>> {
+    #[cfg(unix)]
+    match control::listen(path) {
+        Ok(requests) => return Some(requests),
+        Err(e) => log::warn!("Couldn't open control socket {}: {}", path.display(), e),
+    }
+    #[cfg(not(unix))]
+    log::warn!("Control socket {} needs a unix platform", path.display());
+    None
+}
+
+async fn run_app<B: Backend>(
+    terminal: &mut Terminal<B>,
+    app: &mut App,
+    mut control_requests: Option<Receiver<ControlRequest>>,
+) -> io::Result<()> {
+    let mut resize_debouncer = ResizeDebouncer::new(Duration::from_millis(
+        rynx_browser::constants::RESIZE_DEBOUNCE_MS,
+    ));
+
+    let mut last_title = String::new();
+    let poll_interval = Duration::from_millis(app.config.poll_interval_ms);
+
     loop {
+        // Let transient status messages fall back to "Ready"
+        app.expire_status_messages(Instant::now());
+
+        // Reflow once the terminal has stopped resizing
+        if let Some((width, _height)) = resize_debouncer.take_ready(Instant::now()) {
+            app.resize_all_tabs(width);
+        }
+
+        // A command that borrowed the terminal left the screen out of step with ratatui's buffer
+        if std::mem::take(&mut app.needs_full_redraw) {
+            terminal.clear()?;
+            app.dirty = true;
+        }
+
         let size = terminal.size()?;
 
-        terminal.draw(|f| ui(f, &app))?;
+        // Idle pages aren't redrawn; events, timers and spinners mark the app dirty
+        if app.should_redraw() {
+            terminal.draw(|f| ui(f, app))?;
+        }
+
+        // Mirror the active page title in the terminal window title
+        let active_title = &app.tabs[app.active_tab_index].page_title;
+        if *active_title != last_title {
+            write_terminal_title(&mut io::stdout(), active_title)?;
+            last_title = active_title.clone();
+        }
+
+        // Handle every network event that has arrived, so a busy download can't fill the channel
+        while let Ok(response) = app.rx.try_recv() {
+            handle_network_event::<B>(app, response, size.width)?;
+        }
 
-        // Handle network events
-        if let Ok(response) = app.rx.try_recv() {
-            handle_network_event::<B>(&mut app, response, size.width)?;
+        // Scripted commands are run between frames, like network responses
+        if let Some(requests) = control_requests.as_mut()
+            && !control::handle_requests(app, requests, size.height)
+        {
+            control_requests = None;
         }
 
-        // Handle input events
-        if event::poll(Duration::from_millis(
-            rynx_browser::constants::EVENT_POLL_TIMEOUT_MS,
-        ))? {
+        // Handle input events. Only an idle browser waits out the whole poll interval; with pages
+        // or downloads in flight their messages are picked up at the default pace.
+        let wait = if app.is_busy() {
+            poll_interval.min(Duration::from_millis(
+                rynx_browser::constants::EVENT_POLL_TIMEOUT_MS,
+            ))
+        } else {
+            poll_interval
+        };
+        if event::poll(wait)? {
             match event::read()? {
-                Event::Resize(width, _height) => {
-                    app.resize_all_tabs(width);
+                Event::Resize(width, height) => {
+                    resize_debouncer.record(width, height, Instant::now());
+                    app.dirty = true;
                 }
                 Event::Key(key) => {
-                    if handle_key_event::<B>(&mut app, key, size.width, size.height)? {
+                    if handle_key_event::<B>(app, key, size.width, size.height)? {
                         return Ok(()); // Quit signal received
                     }
                 }
                 Event::Mouse(mouse) => {
-                    handle_mouse_event::<B>(&mut app, mouse, size.width, size.height)?;
+                    handle_mouse_event::<B>(app, mouse, size.width, size.height)?;
                 }
                 _ => {}
             }
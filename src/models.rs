@@ -1,4 +1,5 @@
 use ratatui::text::Line;
+use unicode_width::UnicodeWidthChar;
 
 #[derive(Clone)]
 pub struct LinkRegion {
@@ -6,10 +7,87 @@ pub struct LinkRegion {
     pub line_index: usize,
     pub x_start: usize,
     pub x_end: usize,
+    /// From `rel="noreferrer"`: following it never sends a Referer
+    pub no_referrer: bool,
+}
+
+/// A `<form>` found while rendering, with the fields submitted along with it
+#[derive(Clone, Debug, Default)]
+pub struct Form {
+    pub action: String,
+    /// Lowercased `method` attribute; only "get" forms can be submitted
+    pub method: String,
+    pub fields: Vec<FormField>,
+}
+
+#[derive(Clone, Debug)]
+pub struct FormField {
+    pub name: String,
+    pub value: String,
+    /// Hidden inputs are submitted but can't be focused or typed into
+    pub editable: bool,
+}
+
+/// Where an editable form field was drawn on the page
+#[derive(Clone, Debug)]
+pub struct InputRegion {
+    pub form_index: usize,
+    pub field_index: usize,
+    pub line_index: usize,
+    pub x_start: usize,
+    pub x_end: usize,
+}
+
+/// A back stack entry: the page and how far down it was scrolled when left
+#[derive(Clone, Debug, PartialEq)]
+pub struct HistoryEntry {
+    pub url: String,
+    pub scroll: usize,
+}
+
+/// A page request still in flight, and what was on screen before it started
+#[derive(Clone, Debug)]
+pub struct PendingLoad {
+    /// Cancelling this drops the request before it reports anything else
+    pub cancel: tokio_util::sync::CancellationToken,
+    pub previous_url: String,
+    pub previous_title: String,
+    pub previous_info: Option<PageInfo>,
+}
+
+/// Where a tab's navigation stood when a link check started; its answer only applies if
+/// the tab is still there when it arrives
+#[derive(Clone, Debug)]
+pub struct NavigationSnapshot {
+    pub loaded_url: String,
+    /// Cancelled as soon as that load is replaced or abandoned
+    pub pending: Option<tokio_util::sync::CancellationToken>,
+}
+
+/// A position saved with `m<char>` and returned to with `` `<char> ``
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Mark {
+    pub scroll: usize,
+    pub cursor_line: usize,
+    pub cursor_char: usize,
 }
 
 pub struct PageMetadata {
     pub title: String,
+    /// The `href` of the page's `<base>` element, as written
+    pub base_href: Option<String>,
+    /// The `href` of the first RSS or Atom feed the page advertises with `<link rel="alternate">`
+    pub feed_href: Option<String>,
+}
+
+/// Response metadata for the loaded page, shown in the page info popup
+#[derive(Clone, Debug, Default)]
+pub struct PageInfo {
+    pub final_url: String,
+    pub status: u16,
+    pub content_type: Option<String>,
+    pub content_length: Option<u64>,
+    pub server: Option<String>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -18,6 +96,8 @@ pub enum InputMode {
     Editing,
     Visual,
     Search,
+    FormInput,
+    LinkFilter,
 }
 
 pub struct Selection {
@@ -27,6 +107,16 @@ pub struct Selection {
     pub end_char: usize,
 }
 
+/// The second pane of a split view; the focused pane always shows the active tab
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SplitPane {
+    /// Tab shown in the pane without focus
+    pub tab_index: usize,
+    /// Whether that pane is the upper one
+    pub above: bool,
+}
+
+/// A search hit; `start_char`/`end_char` are display columns, matching link regions
 pub struct SearchMatch {
     pub line_index: usize,
     pub start_char: usize,
@@ -39,9 +129,28 @@ pub struct SearchState {
     pub current_match_index: usize,
 }
 
+/// Links narrowed down by a typed filter; `link_regions` holds the matches meanwhile
+#[derive(Clone)]
+pub struct LinkFilter {
+    pub query: String,
+    /// Every link on the page, put back when filtering ends
+    pub all_links: Vec<LinkRegion>,
+    /// Link selected before filtering started
+    pub previous_selection: usize,
+}
+
 impl Selection {
     /// Extract the selected text from rendered content lines
     pub fn extract_text(&self, rendered_content: &[Line]) -> String {
+        self.extract_text_past_gutter(rendered_content, 0)
+    }
+
+    /// Extract the selected text, leaving out the first `gutter_width` columns of every line
+    pub fn extract_text_past_gutter(
+        &self,
+        rendered_content: &[Line],
+        gutter_width: usize,
+    ) -> String {
         // Normalize selection (handle backwards selection)
         let (s_line, s_char, e_line, e_char) =
             if (self.start_line, self.start_char) <= (self.end_line, self.end_char) {
@@ -64,26 +173,24 @@ impl Selection {
         for i in s_line..=e_line {
             if let Some(line) = rendered_content.get(i) {
                 let line_str = line.to_string();
-                let start = if i == s_line { s_char } else { 0 };
-                let end = if i == e_line {
-                    e_char
-                } else {
-                    line_str.chars().count()
-                };
-
-                // Map char index to byte index for proper UTF-8 handling
-                let byte_start = line_str
-                    .char_indices()
-                    .nth(start)
-                    .map(|(idx, _)| idx)
-                    .unwrap_or(0);
-                let byte_end = line_str
-                    .char_indices()
-                    .nth(end)
-                    .map(|(idx, _)| idx)
-                    .unwrap_or(line_str.len());
-
-                result.push_str(&line_str[byte_start..byte_end]);
+                // Columns are display columns, so wide characters span two cells
+                let start = if i == s_line { s_char } else { 0 }.max(gutter_width);
+                let end = if i == e_line { e_char } else { usize::MAX };
+
+                let mut col = 0;
+                for ch in line_str.chars() {
+                    let width = UnicodeWidthChar::width(ch).unwrap_or(0);
+                    let overlaps = if width == 0 {
+                        col >= start && col < end
+                    } else {
+                        col < end && col + width > start
+                    };
+                    if overlaps {
+                        result.push(ch);
+                    }
+                    col += width;
+                }
+
                 if i < e_line {
                     result.push('\n');
                 }
@@ -93,18 +200,46 @@ impl Selection {
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
 pub enum DownloadStatus {
     Active,
     Completed,
     Failed(String),
+    Cancelled,
 }
 
+#[derive(Clone, Debug)]
 pub struct Download {
-    pub _id: usize,
+    pub id: usize,
+    pub tab_id: usize,
+    pub url: String,
     pub filename: String,
     pub bytes_downloaded: u64,
     pub total_size: Option<u64>,
     pub status: DownloadStatus,
+    pub saved_path: Option<String>,
+    pub started: std::time::Instant,
+    pub last_update: std::time::Instant,
+    /// Cancelling this stops the download task before it writes or reports anything else
+    pub cancel_token: tokio_util::sync::CancellationToken,
+    /// Lowercase hex SHA-256 the finished file must match
+    pub expected_sha256: Option<String>,
+}
+
+impl Download {
+    /// Average transfer rate between the start and the latest progress update
+    pub fn bytes_per_second(&self) -> u64 {
+        let secs = self.last_update.duration_since(self.started).as_secs_f64();
+        if secs > 0.0 {
+            (self.bytes_downloaded as f64 / secs) as u64
+        } else {
+            0
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.status != DownloadStatus::Active
+    }
 }
 
 pub struct DownloadPrompt {
@@ -112,4 +247,46 @@ pub struct DownloadPrompt {
     pub filename: String,
     pub target_path: std::path::PathBuf,
     pub file_exists: bool,
+    /// Set when a running download found an existing file and is waiting on the user
+    pub conflict: Option<DownloadConflict>,
+}
+
+/// Username and password for HTTP Basic auth, kept in memory for the session only
+#[derive(Clone, PartialEq)]
+pub struct BasicCredentials {
+    pub username: String,
+    pub password: String,
+}
+
+impl std::fmt::Debug for BasicCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BasicCredentials")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Login popup shown when a page answers 401 with a Basic challenge
+pub struct AuthPrompt {
+    pub url: String,
+    pub host: String,
+    pub realm: Option<String>,
+    pub username: String,
+    pub password: String,
+    /// Typing goes to the password field instead of the username
+    pub editing_password: bool,
+}
+
+/// How a running download should proceed when its target file already exists
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConflictChoice {
+    Overwrite,
+    Rename,
+    Cancel,
+}
+
+pub struct DownloadConflict {
+    pub download_id: usize,
+    pub reply: tokio::sync::oneshot::Sender<ConflictChoice>,
 }
@@ -1,23 +1,59 @@
 use crate::constants::{
-    BROWSING_TIMEOUT_SECS, DOWNLOAD_TIMEOUT_SECS, I2P_PROXY_URL, JUMP_SERVICES, MAX_REDIRECTS,
-    USER_AGENT,
+    BROWSING_TIMEOUT_SECS, DOWNLOAD_TIMEOUT_SECS, I2P_PROXY_URL, MAX_REDIRECTS, USER_AGENT,
+    USER_AGENT_PRESETS,
 };
-use crate::models::PageMetadata;
+use crate::models::{ConflictChoice, NavigationSnapshot, PageInfo, PageMetadata};
 use reqwest::{Client, StatusCode};
 use scraper::{Html, Selector};
-use std::sync::OnceLock;
-use std::time::Duration;
-use tokio::sync::mpsc;
-use url::Url;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::{mpsc, oneshot};
+use url::{Position, Url};
 
 pub enum NetworkResponse {
-    Success(usize, String, String),
+    Success(usize, String, String, String, Duration), // tab_id, final url, title, html, fetch time
     Error(usize, String),
     Loading(usize),
     Info(usize, String),
     // Variant for downloads
-    DownloadProgress(usize, u64, Option<u64>),
-    DownloadFinished(usize, String), // tab_id, filename
+    DownloadProgress(usize, usize, u64, Option<u64>), // tab_id, download_id, bytes, total
+    DownloadFinished(usize, usize, String),          // tab_id, download_id, saved path
+    DownloadFailed(usize, usize, String),            // tab_id, download_id, error
+    // tab_id, download_id, existing path, where to send the user's decision
+    DownloadConflict(usize, usize, String, oneshot::Sender<ConflictChoice>),
+    // tab_id, url, HEAD verdict (None when the check failed), where the tab was when it started
+    LinkChecked(usize, String, Option<bool>, NavigationSnapshot),
+    // Sent ahead of Success with the page's response metadata
+    ResponseInfo(usize, PageInfo),
+    // tab_id, url, realm: the page wants HTTP Basic credentials
+    AuthRequired(usize, String, Option<String>),
+    // tab_id, url, size limit in bytes: the body was abandoned once it grew past the limit
+    PageTooLarge(usize, String, u64),
+    // tab_id, url, domain, (service, outcome) per jump service tried
+    JumpFailed(usize, String, String, Vec<(String, String)>),
+    // tab_id, url, what's wrong: the server's TLS certificate was refused
+    CertificateError(usize, String, String),
+}
+
+/// The URL one path segment up, dropping any query and fragment.
+/// `https://x/a/b/c` and `https://x/a/b/c/` both become `https://x/a/b/`; the site root has no parent.
+pub fn parent_url(current: &str) -> Option<String> {
+    let mut url = Url::parse(current.trim()).ok()?;
+    if url.cannot_be_a_base() {
+        return None;
+    }
+    let trimmed = url.path().trim_end_matches('/');
+    if trimmed.is_empty() {
+        return None;
+    }
+    let parent = trimmed[..=trimmed.rfind('/')?].to_string();
+    url.set_path(&parent);
+    url.set_query(None);
+    url.set_fragment(None);
+    Some(url.to_string())
 }
 
 /// Resolve relative URLs against a base URL
@@ -46,11 +82,302 @@ pub fn resolve_url(base: &str, target: &str) -> String {
     }
 }
 
+/// Realm of a `WWW-Authenticate: Basic` challenge; `None` when the challenge isn't Basic
+pub fn basic_auth_realm(challenge: &str) -> Option<Option<String>> {
+    let (scheme, params) = challenge
+        .trim()
+        .split_once(char::is_whitespace)
+        .unwrap_or((challenge.trim(), ""));
+    if !scheme.eq_ignore_ascii_case("basic") {
+        return None;
+    }
+    let realm = params.split(',').find_map(|param| {
+        let (key, value) = param.trim().split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case("realm")
+            .then(|| value.trim().trim_matches('"').to_string())
+    });
+    Some(realm)
+}
+
+/// Key credentials are remembered under: host plus port, so two services on one host stay apart
+pub fn auth_host_key(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    Some(format!("{}:{}", url.host_str()?, url.port_or_known_default()?))
+}
+
+/// True for plain `http://` pages that aren't protected by the i2p/onion overlay
+pub fn is_insecure_url(url: &str) -> bool {
+    if !url.starts_with("http://") {
+        return false;
+    }
+    match Url::parse(url).ok().and_then(|u| u.host_str().map(|h| h.to_lowercase())) {
+        Some(host) => !host.ends_with(".i2p") && !host.ends_with(".onion"),
+        None => true,
+    }
+}
+
+/// True for addresses inside the I2P network, which only the proxy can reach
+pub fn is_i2p_url(url: &str) -> bool {
+    Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(|h| h.to_lowercase()))
+        .is_some_and(|host| host.ends_with(".i2p"))
+}
+
+/// Whether text typed in the address bar without a scheme names a site rather than a search.
+/// It must read as a URL once a scheme is added, with no spaces or credentials, and its host
+/// must be `localhost`, an IP address written out in full, or a dotted name whose last label
+/// looks like a top-level domain. `example.com/docs`, `localhost:8080` and `10.0.0.1` qualify;
+/// `rust 1.0 release`, `1.0`, `e.g.` and `v1.2.3` don't.
+pub fn looks_like_address(input: &str) -> bool {
+    let input = input.trim();
+    if input.is_empty() || input.contains(char::is_whitespace) {
+        return false;
+    }
+    let Ok(parsed) = Url::parse(&format!("https://{}", input)) else {
+        return false;
+    };
+    // `user@example.com` is an email address, not a page
+    if !parsed.username().is_empty() || parsed.password().is_some() {
+        return false;
+    }
+    match parsed.host() {
+        Some(url::Host::Domain(domain)) => {
+            if domain == "localhost" {
+                return true;
+            }
+            let mut labels = domain.trim_end_matches('.').split('.');
+            let top_level = labels.next_back().unwrap_or("");
+            let has_name = labels.next().is_some_and(|label| !label.is_empty());
+            has_name
+                && top_level.len() >= 2
+                && top_level.starts_with(|c: char| c.is_ascii_alphabetic())
+                && top_level.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        }
+        // Typed numbers such as `1.0` also parse as (shorthand) IPv4 addresses
+        Some(url::Host::Ipv4(address)) => {
+            let typed = input.split(['/', '?', '#']).next().unwrap_or("");
+            let typed = typed.rsplit_once(':').map_or(typed, |(host, _)| host);
+            typed == address.to_string()
+        }
+        Some(url::Host::Ipv6(_)) => true,
+        None => false,
+    }
+}
+
+/// Remove tracking query parameters from http(s) URLs, keeping everything else intact
+pub fn strip_tracking_params(url: &str, tracking_params: &[String]) -> String {
+    let Ok(mut parsed) = Url::parse(url) else {
+        return url.to_string();
+    };
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.query().is_none() {
+        return url.to_string();
+    }
+
+    let is_tracking = |name: &str| {
+        tracking_params.iter().any(|pattern| match pattern.strip_suffix('*') {
+            Some(prefix) => name.starts_with(prefix),
+            None => name == pattern,
+        })
+    };
+
+    let pairs: Vec<(String, String)> = parsed
+        .query_pairs()
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect();
+    let kept: Vec<&(String, String)> = pairs.iter().filter(|(k, _)| !is_tracking(k)).collect();
+
+    // Leave untouched URLs byte-for-byte as they were
+    if kept.len() == pairs.len() {
+        return url.to_string();
+    }
+
+    if kept.is_empty() {
+        parsed.set_query(None);
+    } else {
+        parsed.query_pairs_mut().clear().extend_pairs(kept);
+    }
+    parsed.to_string()
+}
+
+/// Convert an internationalized domain to its ASCII (punycode) form for the request
+pub fn encode_idn_url(url: &str) -> String {
+    if url.is_ascii() {
+        return url.to_string();
+    }
+    Url::parse(url)
+        .map(|u| u.to_string())
+        .unwrap_or_else(|_| url.to_string())
+}
+
+/// Decode a punycode host back to Unicode so the address bar is readable.
+/// Only the host is decoded; punycode elsewhere in the URL, such as in the userinfo, stays as is.
+pub fn display_url(url: &str) -> String {
+    let Ok(parsed) = Url::parse(url) else {
+        return url.to_string();
+    };
+    let Some(host) = parsed.host_str() else {
+        return url.to_string();
+    };
+    if !host.split('.').any(|label| label.starts_with("xn--")) {
+        return url.to_string();
+    }
+
+    let (unicode_host, result) = idna::domain_to_unicode(host);
+    if result.is_err() {
+        return url.to_string();
+    }
+    // Everything up to the host (scheme and userinfo) and after it (port, path, query and
+    // fragment) comes from the parsed URL, so the host is replaced where it really is
+    format!(
+        "{}{}{}",
+        &parsed[..Position::BeforeHost],
+        unicode_host,
+        &parsed[Position::AfterHost..]
+    )
+}
+
+/// Broad script families used to spot homograph domains like "раypal.com"
+#[derive(Clone, Copy, PartialEq)]
+enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Other,
+}
+
+fn char_script(c: char) -> Option<Script> {
+    match c as u32 {
+        0x41..=0x5A | 0x61..=0x7A | 0xC0..=0x24F => Some(Script::Latin),
+        0x370..=0x3FF => Some(Script::Greek),
+        0x400..=0x52F => Some(Script::Cyrillic),
+        _ if c.is_alphabetic() => Some(Script::Other),
+        _ => None, // Digits, hyphens and dots belong to every script
+    }
+}
+
+/// True when a single label of the (decoded) host mixes letters from different scripts
+pub fn is_mixed_script_domain(host: &str) -> bool {
+    let (unicode_host, _) = idna::domain_to_unicode(host);
+    unicode_host.split('.').any(|label| {
+        let mut scripts = label.chars().filter_map(char_script);
+        match scripts.next() {
+            Some(first) => scripts.any(|script| script != first),
+            None => false,
+        }
+    })
+}
+
+/// A preset name ("firefox", "curl", ...) becomes its UA string; anything else is used verbatim
+pub fn resolve_user_agent(name_or_agent: &str) -> String {
+    let trimmed = name_or_agent.trim();
+    USER_AGENT_PRESETS
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(trimmed))
+        .map(|(_, agent)| agent.to_string())
+        .unwrap_or_else(|| trimmed.to_string())
+}
+
+/// Preset name for a UA string, if it is one of the presets
+pub fn user_agent_preset_name(agent: &str) -> Option<&'static str> {
+    USER_AGENT_PRESETS
+        .iter()
+        .find(|(_, preset)| *preset == agent)
+        .map(|(name, _)| *name)
+}
+
+/// Spaces out requests to the same host by at least `interval`. Each caller reserves the next
+/// free slot for its host and then sleeps until it, so concurrent requests queue up in order.
+pub struct HostThrottle {
+    interval: Duration,
+    next_slot: Mutex<HashMap<String, Instant>>,
+}
+
+impl HostThrottle {
+    pub fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next_slot: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// How long a request to `url` made now has to wait for its turn, reserving that turn
+    pub fn reserve(&self, url: &str) -> Duration {
+        if self.interval.is_zero() {
+            return Duration::ZERO;
+        }
+        let Some(host) = Url::parse(url).ok().and_then(|u| u.host_str().map(str::to_string))
+        else {
+            return Duration::ZERO;
+        };
+        let now = Instant::now();
+        let mut slots = self.next_slot.lock().unwrap_or_else(|e| e.into_inner());
+        // Hosts whose turn has passed are free again
+        slots.retain(|_, slot| *slot > now);
+        let slot = slots.get(&host).copied().unwrap_or(now);
+        slots.insert(host, slot + self.interval);
+        slot - now
+    }
+
+    /// Wait until a request to `url` is allowed; only ever called from a spawned task
+    pub async fn wait(&self, url: &str) {
+        let delay = self.reserve(url);
+        if !delay.is_zero() {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
 pub struct NetworkManager {
+    user_agent: String,
+    /// Shared with the managers this one replaces, so rebuilding keeps every host's spacing
+    throttle: Arc<HostThrottle>,
     client: Client,
     i2p_client: Client,
     download_client: Client,
     i2p_download_client: Client,
+    /// Skip certificate checks; only used for hosts the user chose to trust anyway
+    insecure_client: Client,
+    i2p_insecure_client: Client,
+}
+
+/// Name resolver for direct clients that leaves out loopback, private and link-local addresses.
+/// The connection uses exactly the addresses checked here, so a name can't answer one lookup
+/// with a public address and the next with an internal one.
+struct PublicOnlyResolver;
+
+impl reqwest::dns::Resolve for PublicOnlyResolver {
+    fn resolve(&self, name: reqwest::dns::Name) -> reqwest::dns::Resolving {
+        Box::pin(public_addrs(name.as_str().to_string()))
+    }
+}
+
+async fn public_addrs(
+    host: String,
+) -> Result<reqwest::dns::Addrs, Box<dyn std::error::Error + Send + Sync>> {
+    // The port is filled in by the client
+    let public = resolve_allowed(&host, 0, false).await?;
+    let addrs: reqwest::dns::Addrs = Box::new(public.into_iter());
+    Ok(addrs)
+}
+
+/// Look up `host` and keep the addresses a connection may go to: all of them with
+/// `allow_local_targets`, otherwise only public ones. Connections made outside reqwest, like
+/// Gemini's, go to exactly these addresses rather than looking the name up again.
+pub async fn resolve_allowed(
+    host: &str,
+    port: u16,
+    allow_local_targets: bool,
+) -> std::io::Result<Vec<SocketAddr>> {
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host, port))
+        .await?
+        .filter(|addr| allow_local_targets || !is_internal_ip(addr.ip()))
+        .collect();
+    if addrs.is_empty() {
+        return Err(std::io::Error::other(local_target_message(host)));
+    }
+    Ok(addrs)
 }
 
 impl NetworkManager {
@@ -60,8 +387,14 @@ impl NetworkManager {
         timeout: Duration,
         use_proxy: bool,
         include_headers: bool,
+        allow_https_downgrade: bool,
+        accept_invalid_certs: bool,
+        allow_local_targets: bool,
     ) -> Result<Client, Box<dyn std::error::Error + Send + Sync>> {
-        let mut builder = Client::builder().user_agent(user_agent).timeout(timeout);
+        let mut builder = Client::builder()
+            .user_agent(user_agent)
+            .timeout(timeout)
+            .danger_accept_invalid_certs(accept_invalid_certs);
 
         // Enforce TLS 1.2 as minimum for security (only for non-I2P clients)
         if !use_proxy {
@@ -79,51 +412,125 @@ impl NetworkManager {
         if use_proxy {
             let proxy = reqwest::Proxy::http(I2P_PROXY_URL)?;
             builder = builder.proxy(proxy);
+        } else if !allow_local_targets {
+            // Proxied names are resolved by the proxy; direct ones must stay off the local network
+            builder = builder.dns_resolver(Arc::new(PublicOnlyResolver));
         }
 
-        // Always apply redirect policy for browsing clients
+        // Always apply redirect policy for browsing clients. A redirect keeps the request's own
+        // Referer rather than naming the page that redirected, so `noreferrer` links stay bare.
         if include_headers {
-            builder = builder.redirect(strict_redirect_policy());
+            let policy = if accept_invalid_certs {
+                trusted_host_redirect_policy(allow_https_downgrade)
+            } else {
+                strict_redirect_policy(allow_https_downgrade)
+            };
+            builder = builder.redirect(policy).referer(false);
+        } else {
+            // Downloads may still move to plain HTTP mirrors, but not onto the local network
+            builder = builder.redirect(strict_redirect_policy(true));
         }
 
         Ok(builder.build()?)
     }
 
     pub fn new() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
-        // Create all four clients using the build_client helper method
+        Self::with_user_agent(USER_AGENT)
+    }
+
+    /// Build the clients with `user_agent` sent on every page and download request
+    pub fn with_user_agent(
+        user_agent: &str,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let throttle = Arc::new(HostThrottle::new(Duration::ZERO));
+        Self::with_settings(user_agent, false, false, throttle)
+    }
+
+    /// Like `with_user_agent`, optionally letting page redirects go from HTTPS to plain HTTP
+    /// and direct requests reach names that resolve to local addresses, and spacing page
+    /// requests with `throttle`
+    pub fn with_settings(
+        user_agent: &str,
+        allow_https_downgrade: bool,
+        allow_local_targets: bool,
+        throttle: Arc<HostThrottle>,
+    ) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        // Create every client using the build_client helper method
         let client = Self::build_client(
-            USER_AGENT,
+            user_agent,
             Duration::from_secs(BROWSING_TIMEOUT_SECS),
             false,
             true,
+            allow_https_downgrade,
+            false,
+            allow_local_targets,
         )?;
         let i2p_client = Self::build_client(
-            USER_AGENT,
+            user_agent,
             Duration::from_secs(BROWSING_TIMEOUT_SECS),
             true,
             true,
+            allow_https_downgrade,
+            false,
+            allow_local_targets,
         )?;
         let download_client = Self::build_client(
-            USER_AGENT,
+            user_agent,
             Duration::from_secs(DOWNLOAD_TIMEOUT_SECS),
             false,
             false,
+            allow_https_downgrade,
+            false,
+            allow_local_targets,
         )?;
         let i2p_download_client = Self::build_client(
-            USER_AGENT,
+            user_agent,
             Duration::from_secs(DOWNLOAD_TIMEOUT_SECS),
             true,
             false,
+            allow_https_downgrade,
+            false,
+            allow_local_targets,
+        )?;
+        let insecure_client = Self::build_client(
+            user_agent,
+            Duration::from_secs(BROWSING_TIMEOUT_SECS),
+            false,
+            true,
+            allow_https_downgrade,
+            true,
+            allow_local_targets,
+        )?;
+        let i2p_insecure_client = Self::build_client(
+            user_agent,
+            Duration::from_secs(BROWSING_TIMEOUT_SECS),
+            true,
+            true,
+            allow_https_downgrade,
+            true,
+            allow_local_targets,
         )?;
 
         Ok(Self {
+            user_agent: user_agent.to_string(),
+            throttle,
             client,
             i2p_client,
             download_client,
             i2p_download_client,
+            insecure_client,
+            i2p_insecure_client,
         })
     }
 
+    pub fn user_agent(&self) -> &str {
+        &self.user_agent
+    }
+
+    pub fn throttle(&self) -> &HostThrottle {
+        &self.throttle
+    }
+
     pub fn get_client(&self, i2p_mode: bool) -> &Client {
         if i2p_mode {
             &self.i2p_client
@@ -132,6 +539,16 @@ impl NetworkManager {
         }
     }
 
+    /// Browsing client that accepts any certificate, for hosts trusted despite a problem.
+    /// In I2P mode it still goes through the proxy.
+    pub fn insecure_client(&self, i2p_mode: bool) -> &Client {
+        if i2p_mode {
+            &self.i2p_insecure_client
+        } else {
+            &self.insecure_client
+        }
+    }
+
     pub fn get_download_client(&self, i2p_mode: bool) -> &Client {
         if i2p_mode {
             &self.i2p_download_client
@@ -144,64 +561,831 @@ impl NetworkManager {
 pub fn parse_html_metadata(html: &str) -> PageMetadata {
     let document = Html::parse_document(html);
     static TITLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+    static H1_SELECTOR: OnceLock<Selector> = OnceLock::new();
+    static H2_SELECTOR: OnceLock<Selector> = OnceLock::new();
+    static BASE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+    static FEED_SELECTOR: OnceLock<Selector> = OnceLock::new();
     let title_selector = TITLE_SELECTOR.get_or_init(|| Selector::parse("title").unwrap());
+    let h1_selector = H1_SELECTOR.get_or_init(|| Selector::parse("h1").unwrap());
+    let h2_selector = H2_SELECTOR.get_or_init(|| Selector::parse("h2").unwrap());
+    let base_selector = BASE_SELECTOR.get_or_init(|| Selector::parse("base[href]").unwrap());
+    let feed_selector = FEED_SELECTOR.get_or_init(|| {
+        Selector::parse(
+            r#"link[rel~="alternate"][type="application/rss+xml"][href],
+               link[rel~="alternate"][type="application/atom+xml"][href]"#,
+        )
+        .unwrap()
+    });
 
-    let title = document
-        .select(title_selector)
-        .next()
-        .map(|element| {
-            element
-                .text()
-                .collect::<Vec<_>>()
-                .join(" ")
-                .trim()
-                .to_string()
+    // Prefer <title>, then fall back to the first <h1> and <h2> for titleless pages
+    let title = [title_selector, h1_selector, h2_selector]
+        .iter()
+        .find_map(|selector| {
+            document.select(selector).next().and_then(|element| {
+                let text = element.text().collect::<Vec<_>>().join(" ");
+                let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+                (!text.is_empty()).then_some(text)
+            })
         })
         .unwrap_or_else(|| "No Title".to_string());
 
-    PageMetadata { title }
+    // Only the first <base> with an href counts
+    let base_href = document
+        .select(base_selector)
+        .next()
+        .and_then(|element| element.value().attr("href"))
+        .map(|href| href.trim().to_string())
+        .filter(|href| !href.is_empty());
+
+    let feed_href = document
+        .select(feed_selector)
+        .next()
+        .and_then(|element| element.value().attr("href"))
+        .map(|href| href.trim().to_string())
+        .filter(|href| !href.is_empty());
+
+    PageMetadata {
+        title,
+        base_href,
+        feed_href,
+    }
 }
 
-pub fn strict_redirect_policy() -> reqwest::redirect::Policy {
-    reqwest::redirect::Policy::custom(|attempt| {
-        if attempt.previous().len() > MAX_REDIRECTS {
-            return attempt.error("Too many redirects");
+pub fn strict_redirect_policy(allow_https_downgrade: bool) -> reqwest::redirect::Policy {
+    reqwest::redirect::Policy::custom(move |attempt| {
+        match redirect_violation(attempt.previous(), attempt.url(), allow_https_downgrade) {
+            Some(reason) => attempt.error(reason),
+            None => attempt.follow(),
         }
-        if let Some(host) = attempt.url().host_str() {
-            if host == "localhost" || host == "127.0.0.1" || host == "::1" {
-                return attempt.error("Blocked redirect to local network (SSRF Protection)");
-            }
+    })
+}
+
+/// `strict_redirect_policy` for clients that skip certificate checks. A redirect may not leave
+/// the `host:port` the request started on, since only that one was trusted despite its certificate.
+pub fn trusted_host_redirect_policy(allow_https_downgrade: bool) -> reqwest::redirect::Policy {
+    reqwest::redirect::Policy::custom(move |attempt| {
+        let trusted = attempt.previous().first().and_then(|url| auth_host_key(url.as_str()));
+        if let Some(reason) =
+            redirect_violation(attempt.previous(), attempt.url(), allow_https_downgrade)
+        {
+            attempt.error(reason)
+        } else if auth_host_key(attempt.url().as_str()) != trusted {
+            attempt.error(String::from("Blocked redirect away from a host trusted anyway"))
+        } else {
+            attempt.follow()
         }
-        attempt.follow()
     })
 }
 
+/// Loopback, private, link-local, shared (CGNAT), benchmarking, broadcast and unspecified
+/// addresses: anything that isn't the public internet. IPv4 wrapped in IPv6 is judged as the
+/// IPv4 address it reaches.
+pub fn is_internal_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, c, _] = v4.octets();
+            v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_broadcast()
+                || a == 0 // "this network", which Linux routes to the local host
+                || (a == 100 && (b & 0xc0) == 64) // shared address space (CGNAT)
+                || (a == 192 && b == 0 && c == 0) // IETF protocol assignments
+                || (a == 198 && (b & 0xfe) == 18) // benchmarking
+        }
+        IpAddr::V6(v6) => {
+            if let Some(v4) = embedded_ipv4(v6) {
+                return is_internal_ip(IpAddr::V4(v4));
+            }
+            let segments = v6.segments();
+            let first = segments[0];
+            v6.is_loopback()
+                || v6.is_unspecified()
+                || (first & 0xfe00) == 0xfc00 // unique local
+                || (first & 0xffc0) == 0xfe80 // link-local
+                || segments[..3] == [0x64, 0xff9b, 1] // local-use NAT64
+        }
+    }
+}
+
+/// The IPv4 address carried by an IPv4-mapped (`::ffff:a.b.c.d`), IPv4-compatible
+/// (`::a.b.c.d`), IPv4-translated (`::ffff:0:a.b.c.d`) or NAT64 (`64:ff9b::a.b.c.d`) address
+fn embedded_ipv4(v6: Ipv6Addr) -> Option<Ipv4Addr> {
+    // The IPv4 address is always the last 32 bits
+    let v4 = Ipv4Addr::from(u128::from(v6) as u32);
+    match &v6.segments()[..6] {
+        [0, 0, 0, 0, 0, 0 | 0xffff] | [0, 0, 0, 0, 0xffff, 0] | [0x64, 0xff9b, 0, 0, 0, 0] => {
+            Some(v4)
+        }
+        _ => None,
+    }
+}
+
+fn is_internal_host(host: &str) -> bool {
+    if host == "localhost" || host.ends_with(".localhost") {
+        return true;
+    }
+    host.trim_start_matches('[')
+        .trim_end_matches(']')
+        .parse()
+        .is_ok_and(is_internal_ip)
+}
+
+fn local_target_message(host: &str) -> String {
+    format!(
+        "Blocked request to local network address {} (SSRF Protection)",
+        host
+    )
+}
+
+/// Why a request to `url` must not be sent, if it mustn't: the host is `localhost` or an
+/// internal address written out. Other names are checked as the client resolves them.
+pub fn target_violation(url: &Url) -> Option<String> {
+    let host = url.host_str()?;
+    is_internal_host(host).then(|| local_target_message(host))
+}
+
+/// The Referer for a request to `target` made from the page at `source`: just the page's origin,
+/// and nothing when leaving HTTPS for plain HTTP. The path stays out even on the same origin,
+/// since a redirect there could carry the header on to another site unchanged.
+pub fn referer_for(source: &str, target: &str) -> Option<String> {
+    let source = Url::parse(source).ok()?;
+    let target = Url::parse(target).ok()?;
+    if !matches!(source.scheme(), "http" | "https")
+        || (source.scheme() == "https" && target.scheme() != "https")
+    {
+        return None;
+    }
+    Some(format!("{}/", source.origin().ascii_serialization()))
+}
+
+/// Why the redirect from the last URL in `previous` to `next` must not be followed, if it mustn't.
+/// Only http(s) targets are allowed, HTTPS may not drop to HTTP unless `allow_https_downgrade`,
+/// and a chain that started on a public host may not reach the local machine or network.
+pub fn redirect_violation(
+    previous: &[Url],
+    next: &Url,
+    allow_https_downgrade: bool,
+) -> Option<String> {
+    if previous.len() > MAX_REDIRECTS {
+        return Some(String::from("Too many redirects"));
+    }
+    if !matches!(next.scheme(), "http" | "https") {
+        return Some(format!("Blocked redirect to non-web scheme {}:", next.scheme()));
+    }
+    if !allow_https_downgrade
+        && next.scheme() == "http"
+        && previous.last().is_some_and(|from| from.scheme() == "https")
+    {
+        return Some(String::from("Blocked redirect from HTTPS to HTTP"));
+    }
+    let started_local = previous
+        .first()
+        .and_then(|first| first.host_str())
+        .is_some_and(is_internal_host);
+    if !started_local && next.host_str().is_some_and(is_internal_host) {
+        return Some(String::from("Blocked redirect to local network (SSRF Protection)"));
+    }
+    None
+}
+
+/// Ask each jump service in turn for `target_domain`, returning the first `200 OK` answer.
+/// Each gets `timeout` to answer, so one that hangs doesn't hold up the rest; `.i2p` services
+/// always go through the proxy. On failure every service tried is listed with what went wrong.
 pub async fn attempt_jump(
-    client: &Client,
+    network_manager: &NetworkManager,
+    use_i2p: bool,
+    services: &[String],
+    timeout: Duration,
     target_domain: &str,
     tx: mpsc::Sender<NetworkResponse>,
     id: usize,
-) -> Result<reqwest::Response, Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<reqwest::Response, Vec<(String, String)>> {
     let _ = tx
         .send(NetworkResponse::Info(
             id,
             "Address not found. Requesting Jump Helper...".to_string(),
         ))
         .await;
-    for service_base in JUMP_SERVICES {
+    let mut failures = Vec::new();
+    for service_base in services {
         let jump_url = format!("{}{}", service_base, target_domain);
+        log::info!("jump service {} for {}", service_base, target_domain);
         let _ = tx
             .send(NetworkResponse::Info(
                 id,
                 format!("Contacting jump service: {}", service_base),
             ))
             .await;
-        let response = client.get(&jump_url).send().await?;
-        if response.status() == StatusCode::OK {
-            return Ok(response);
+        let client = network_manager.get_client(use_i2p || is_i2p_url(&jump_url));
+        // Only the answer is timed; the resolved page's body loads under the usual timeout
+        match tokio::time::timeout(timeout, client.get(&jump_url).send()).await {
+            Ok(Ok(response)) if response.status() == StatusCode::OK => return Ok(response),
+            Ok(Ok(response)) => {
+                log::debug!("jump service {} answered {}", service_base, response.status());
+                failures.push((service_base.clone(), response.status().to_string()));
+            }
+            Ok(Err(e)) => {
+                log::debug!("jump service {} failed: {}", service_base, e);
+                failures.push((service_base.clone(), describe_fetch_error(&e)));
+            }
+            Err(_) => {
+                log::debug!("jump service {} timed out", service_base);
+                let reason = format!("Timeout: no answer within {:?}", timeout);
+                failures.push((service_base.clone(), reason));
+            }
         }
     }
-    Err("All jump services failed.".into())
+    log::warn!("all jump services failed for {}", target_domain);
+    Err(failures)
+}
+
+/// Determines if a URL likely points to a downloadable file based on extension or patterns
+pub fn is_downloadable_file(url: &str) -> bool {
+    let u = url.to_lowercase();
+    // Restored common types that users expect to download via click
+    let binary_exts = [
+        "zip", "pdf", "exe", "dmg", "pkg", "deb", "iso", "mp4", "mp3",
+        "png", "jpg", "jpeg", "gif", "docx", "xlsx", "tar", "gz", "ogg", "txt"
+    ];
+
+    if let Some(dot) = u.rfind('.') {
+        let ext = u[dot + 1..].split('?').next().unwrap_or("");
+        if binary_exts.contains(&ext) { return true; }
+    }
+
+    // Catch common dynamic download paths
+    ["/download/", "/files/", "/assets/", "/attachments/"].iter().any(|p| u.contains(p))
+}
+
+
+/// The field delimiter when a response is CSV or TSV rather than HTML.
+/// The Content-Type decides when it names a table format or HTML; otherwise the URL's extension does.
+pub fn table_delimiter(content_type: Option<&str>, url: &str) -> Option<char> {
+    let mime = content_type
+        .and_then(|ct| ct.split(';').next())
+        .map(|mime| mime.trim().to_ascii_lowercase());
+    match mime.as_deref() {
+        Some("text/csv" | "application/csv") => return Some(','),
+        Some("text/tab-separated-values") => return Some('\t'),
+        Some("text/html" | "application/xhtml+xml") => return None,
+        _ => {}
+    }
+    let path = Url::parse(url)
+        .map(|u| u.path().to_ascii_lowercase())
+        .unwrap_or_default();
+    if path.ends_with(".csv") {
+        Some(',')
+    } else if path.ends_with(".tsv") {
+        Some('\t')
+    } else {
+        None
+    }
+}
+
+/// Whether a response is Markdown, judged like `table_delimiter`: Content-Type first, then extension
+pub fn is_markdown(content_type: Option<&str>, url: &str) -> bool {
+    let mime = content_type
+        .and_then(|ct| ct.split(';').next())
+        .map(|mime| mime.trim().to_ascii_lowercase());
+    match mime.as_deref() {
+        Some("text/markdown" | "text/x-markdown") => return true,
+        Some("text/html" | "application/xhtml+xml") => return false,
+        _ => {}
+    }
+    let path = Url::parse(url)
+        .map(|u| u.path().to_ascii_lowercase())
+        .unwrap_or_default();
+    path.ends_with(".md") || path.ends_with(".markdown")
+}
+
+/// Convert Markdown to HTML so it renders through the usual DOM pipeline
+pub fn markdown_to_html(text: &str) -> String {
+    let options =
+        pulldown_cmark::Options::ENABLE_TABLES | pulldown_cmark::Options::ENABLE_STRIKETHROUGH;
+    let mut html = String::new();
+    pulldown_cmark::html::push_html(&mut html, pulldown_cmark::Parser::new_ext(text, options));
+    html
+}
+
+/// Whether a response is XML and so may be a sitemap or feed, by Content-Type or path
+pub fn is_xml(content_type: Option<&str>, url: &str) -> bool {
+    let mime = content_type
+        .and_then(|ct| ct.split(';').next())
+        .map(|mime| mime.trim().to_ascii_lowercase())
+        .unwrap_or_default();
+    if matches!(
+        mime.as_str(),
+        "text/xml" | "application/xml" | "application/rss+xml" | "application/atom+xml"
+    ) {
+        return true;
+    }
+    Url::parse(url).is_ok_and(|u| {
+        let path = u.path().to_ascii_lowercase();
+        path.ends_with(".xml") || path.ends_with(".rss") || path.ends_with(".atom")
+    })
+}
+
+/// List the `<loc>` entries of a sitemap (`<urlset>`) or sitemap index (`<sitemapindex>`) as links.
+/// An index links to its child sitemaps, which open as sitemaps in turn.
+/// `None` when the XML is something else.
+pub fn sitemap_to_html(xml: &str) -> Option<String> {
+    static INDEX_SELECTOR: OnceLock<Selector> = OnceLock::new();
+    static URLSET_SELECTOR: OnceLock<Selector> = OnceLock::new();
+    static LOC_SELECTOR: OnceLock<Selector> = OnceLock::new();
+    let index_selector = INDEX_SELECTOR.get_or_init(|| Selector::parse("sitemapindex").unwrap());
+    let urlset_selector = URLSET_SELECTOR.get_or_init(|| Selector::parse("urlset").unwrap());
+    let loc_selector = LOC_SELECTOR.get_or_init(|| Selector::parse("loc").unwrap());
+
+    let document = Html::parse_document(xml);
+    let (title, noun) = if document.select(index_selector).next().is_some() {
+        ("Sitemap Index", "sitemaps")
+    } else if document.select(urlset_selector).next().is_some() {
+        ("Sitemap", "pages")
+    } else {
+        return None;
+    };
+
+    let locations: Vec<String> = document
+        .select(loc_selector)
+        .map(|loc| loc.text().collect::<String>().trim().to_string())
+        .filter(|loc| !loc.is_empty())
+        .collect();
+
+    let mut html = format!(
+        "<html><head><title>{title}</title></head><body><h1>{title}</h1><p>{} {noun}</p><ul>",
+        locations.len()
+    );
+    for loc in &locations {
+        let loc = escape_html(loc);
+        html.push_str(&format!("<li><a href=\"{loc}\">{loc}</a></li>"));
+    }
+    html.push_str("</ul></body></html>");
+    Some(html)
+}
+
+/// List the items of an RSS `<channel>` or the entries of an Atom `<feed>` as links with their
+/// summaries. `None` when the XML is something else.
+pub fn feed_to_html(xml: &str) -> Option<String> {
+    static CHANNEL_SELECTOR: OnceLock<Selector> = OnceLock::new();
+    static FEED_SELECTOR: OnceLock<Selector> = OnceLock::new();
+    static ITEM_SELECTOR: OnceLock<Selector> = OnceLock::new();
+    let channel_selector = CHANNEL_SELECTOR.get_or_init(|| Selector::parse("channel").unwrap());
+    let feed_selector = FEED_SELECTOR.get_or_init(|| Selector::parse("feed").unwrap());
+    let item_selector = ITEM_SELECTOR.get_or_init(|| Selector::parse("item, entry").unwrap());
+
+    // The HTML parser drops CDATA sections, which feeds use for titles and summaries
+    let document = Html::parse_document(&expand_cdata(xml));
+    let root = document
+        .select(channel_selector)
+        .next()
+        .or_else(|| document.select(feed_selector).next())?;
+
+    let title = child_text(root, "title").unwrap_or_else(|| String::from("Feed"));
+    let title = escape_html(&title);
+    let mut html = format!("<html><head><title>{title}</title></head><body><h1>{title}</h1>");
+    if let Some(about) = child_text(root, "description").or_else(|| child_text(root, "subtitle")) {
+        html.push_str(&format!("<p>{}</p>", escape_html(&about)));
+    }
+    for item in document.select(item_selector) {
+        let heading = child_text(item, "title").unwrap_or_else(|| String::from("Untitled"));
+        let heading = escape_html(&heading);
+        match feed_item_link(item) {
+            Some(link) => {
+                html.push_str(&format!("<h2><a href=\"{}\">{heading}</a></h2>", escape_html(&link)))
+            }
+            None => html.push_str(&format!("<h2>{heading}</h2>")),
+        }
+        let date = ["pubdate", "updated", "published"]
+            .iter()
+            .find_map(|name| child_text(item, name));
+        if let Some(date) = date {
+            html.push_str(&format!("<p><i>{}</i></p>", escape_html(&date)));
+        }
+        let summary = ["description", "summary", "content"]
+            .iter()
+            .find_map(|name| child_text(item, name));
+        if let Some(summary) = summary {
+            html.push_str(&format!("<p>{}</p>", escape_html(&summary)));
+        }
+    }
+    html.push_str("</body></html>");
+    Some(html)
+}
+
+/// Replace `<![CDATA[...]]>` sections with their escaped text
+fn expand_cdata(xml: &str) -> String {
+    let mut out = String::with_capacity(xml.len());
+    let mut rest = xml;
+    while let Some(start) = rest.find("<![CDATA[") {
+        out.push_str(&rest[..start]);
+        let inner = &rest[start + "<![CDATA[".len()..];
+        let end = inner.find("]]>").unwrap_or(inner.len());
+        out.push_str(&escape_html(&inner[..end]));
+        rest = inner.get(end + "]]>".len()..).unwrap_or("");
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Plain text of the first direct child named `name`. Summaries often carry escaped
+/// markup, so that is parsed and reduced to its text too.
+fn child_text(parent: scraper::ElementRef, name: &str) -> Option<String> {
+    let child = parent
+        .children()
+        .filter_map(scraper::ElementRef::wrap)
+        .find(|child| child.value().name() == name)?;
+    let raw: String = child.text().collect();
+    let fragment = Html::parse_fragment(&raw);
+    let text = fragment.root_element().text().collect::<Vec<_>>().join(" ");
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!text.is_empty()).then_some(text)
+}
+
+/// Atom puts the URL in `<link href>`. RSS writes `<link>url</link>`, which the HTML
+/// parser treats as an empty `<link>` followed by the URL as text.
+fn feed_item_link(item: scraper::ElementRef) -> Option<String> {
+    let link = item
+        .children()
+        .filter_map(scraper::ElementRef::wrap)
+        .find(|child| {
+            child.value().name() == "link"
+                && child.value().attr("rel").is_none_or(|rel| rel == "alternate")
+        });
+    let url = match link {
+        Some(link) => link.value().attr("href").map(str::to_string).or_else(|| {
+            link.next_sibling()
+                .and_then(|node| node.value().as_text().map(|text| text.to_string()))
+        }),
+        None => None,
+    };
+    url.or_else(|| child_text(item, "guid"))
+        .map(|url| url.trim().to_string())
+        .filter(|url| url.starts_with("http://") || url.starts_with("https://"))
+}
+
+/// Whether a link's URL alone can't tell a page from a file: no extension to go on,
+/// or a query string that may change what the server sends back
+pub fn is_ambiguous_link(url: &str) -> bool {
+    let Ok(parsed) = Url::parse(url) else {
+        return false;
+    };
+    if !matches!(parsed.scheme(), "http" | "https") {
+        return false;
+    }
+    let last_segment = parsed.path().rsplit('/').next().unwrap_or("");
+    if last_segment.is_empty() {
+        // Directory-style paths are pages unless a query says otherwise
+        return parsed.query().is_some();
+    }
+    !last_segment.contains('.') || parsed.query().is_some()
+}
+
+/// Ask the server what a link is with a HEAD request.
+/// `Some(true)` means download, `Some(false)` means render, `None` means the server didn't say.
+pub async fn probe_link_is_download(client: &Client, url: &str) -> Option<bool> {
+    let res = client.head(url).send().await.ok()?;
+    if !res.status().is_success() {
+        return None;
+    }
+    let headers = res.headers();
+    let disposition = headers
+        .get(reqwest::header::CONTENT_DISPOSITION)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.trim().to_ascii_lowercase());
+    if disposition.is_some_and(|d| d.starts_with("attachment")) {
+        return Some(true);
+    }
+    let content_type = headers
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())?
+        .to_ascii_lowercase();
+    Some(!is_renderable(Some(&content_type), url))
+}
+
+/// Whether a response opens as a page rather than a download: HTML, or one of the formats
+/// turned into a page (CSV/TSV tables, Markdown, sitemaps and feeds)
+pub fn is_renderable(content_type: Option<&str>, url: &str) -> bool {
+    let mime = content_type
+        .and_then(|ct| ct.split(';').next())
+        .map(|mime| mime.trim().to_ascii_lowercase());
+    matches!(mime.as_deref(), Some("text/html" | "application/xhtml+xml"))
+        || table_delimiter(content_type, url).is_some()
+        || is_markdown(content_type, url)
+        || is_xml(content_type, url)
+}
+
+/// Describe a failed fetch as `<category>: <details>`, where the category is one of
+/// Timeout, DNS error, TLS error, Connection failed, Redirect error, HTTP <code> or Request failed
+pub fn describe_fetch_error(err: &reqwest::Error) -> String {
+    // reqwest's own message rarely says why; the cause chain does
+    let mut details = err.to_string();
+    let mut source = std::error::Error::source(err);
+    while let Some(cause) = source {
+        details.push_str(": ");
+        details.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    let lower = details.to_lowercase();
+
+    let category = if err.is_timeout() {
+        String::from("Timeout")
+    } else if let Some(status) = err.status() {
+        format!("HTTP {}", status.as_u16())
+    } else if err.is_redirect() {
+        String::from("Redirect error")
+    } else if lower.contains("ssrf protection") {
+        String::from("Blocked")
+    } else if lower.contains("dns") || lower.contains("lookup") || lower.contains("resolve") {
+        String::from("DNS error")
+    } else if lower.contains("certificate") || lower.contains("tls") || lower.contains("handshake") {
+        String::from("TLS error")
+    } else if err.is_connect() {
+        String::from("Connection failed")
+    } else {
+        String::from("Request failed")
+    };
+    format!("{}: {}", category, details)
+}
+
+/// What was wrong with the server's certificate, when that's why a request failed
+pub fn certificate_problem(err: &reqwest::Error) -> Option<String> {
+    use tokio_rustls::rustls::{self, CertificateError};
+
+    // rustls errors come wrapped in io::Errors somewhere down the cause chain
+    let mut source: Option<&(dyn std::error::Error + 'static)> = Some(err);
+    while let Some(cause) = source {
+        if let Some(rustls::Error::InvalidCertificate(problem)) = cause.downcast_ref() {
+            let description = match problem {
+                CertificateError::Expired | CertificateError::ExpiredContext { .. } => {
+                    String::from("The certificate has expired")
+                }
+                CertificateError::NotValidYet | CertificateError::NotValidYetContext { .. } => {
+                    String::from("The certificate isn't valid yet")
+                }
+                CertificateError::NotValidForName
+                | CertificateError::NotValidForNameContext { .. } => {
+                    String::from("The certificate is for a different host name")
+                }
+                CertificateError::UnknownIssuer => {
+                    String::from("The certificate isn't signed by a trusted authority")
+                }
+                CertificateError::Revoked => String::from("The certificate has been revoked"),
+                other => format!("The certificate is invalid: {}", other),
+            };
+            return Some(description);
+        }
+        // An io::Error's source() skips the error it wraps, so step into that directly
+        source = match cause.downcast_ref::<std::io::Error>() {
+            Some(io) => io.get_ref().map(|inner| inner as &(dyn std::error::Error + 'static)),
+            None => cause.source(),
+        };
+    }
+    None
+}
+
+/// Collect the metadata shown in the page info popup from a response
+pub fn page_info_from_response(resp: &reqwest::Response) -> PageInfo {
+    let header = |name: reqwest::header::HeaderName| {
+        resp.headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string())
+    };
+    PageInfo {
+        final_url: resp.url().to_string(),
+        status: resp.status().as_u16(),
+        content_type: header(reqwest::header::CONTENT_TYPE),
+        content_length: resp.content_length(),
+        server: header(reqwest::header::SERVER),
+    }
+}
+
+/// Escape text for interpolation into generated HTML
+pub fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+/// Error page for a failed fetch, with a Retry link back to the URL that failed
+pub fn error_page_html(url: &str, message: &str) -> String {
+    let url = escape_html(url);
+    format!(
+        "<h1>Error</h1><hr>\
+         <p>Could not load <b>{url}</b></p>\
+         <p style='color:red'>{}</p>\
+         <p><a href=\"{url}\">Retry</a></p>",
+        escape_html(message)
+    )
+}
+
+/// Security warning for a page whose certificate was refused. `retry_key` is the key that loads
+/// it anyway, when the config allows that.
+pub fn certificate_error_page_html(url: &str, problem: &str, retry_key: Option<&str>) -> String {
+    let url = escape_html(url);
+    let retry = match retry_key {
+        Some(key) => format!(
+            "<p>If you trust this site anyway, press <b>{}</b> to load it without checking \
+             its certificate. That lasts for this host until the browser exits.</p>",
+            escape_html(key)
+        ),
+        None => String::from(
+            "<p>Loading it anyway is turned off; see <b>allow_insecure_retry</b>.</p>",
+        ),
+    };
+    format!(
+        "<h1>Security Warning</h1><hr>\
+         <p>The connection to <b>{url}</b> isn't secure.</p>\
+         <p style='color:red'>{}</p>\
+         <p>Someone may be impersonating the site, or it may be misconfigured. \
+         The page wasn't loaded.</p>\
+         {retry}\
+         <p><a href=\"{url}\">Retry</a></p>",
+        escape_html(problem)
+    )
+}
+
+/// Shown when an I2P address couldn't be found and no jump service knew it either
+pub fn jump_failure_page_html(url: &str, domain: &str, attempts: &[(String, String)]) -> String {
+    let url = escape_html(url);
+    let tried: String = attempts
+        .iter()
+        .map(|(service, outcome)| {
+            format!("<li>{}: {}</li>", escape_html(service), escape_html(outcome))
+        })
+        .collect();
+    format!(
+        "<h1>Address Not Found</h1><hr>\
+         <p>The I2P address <b>{}</b> couldn't be resolved.</p>\
+         <p>No jump service knew it. Services tried:</p>\
+         <ul>{tried}</ul>\
+         <p>Check the address, or add it to your router's address book.</p>\
+         <p><a href=\"{url}\">Retry</a></p>",
+        escape_html(domain)
+    )
+}
+
+/// Shown instead of a page whose body exceeds the size limit; `d` on the link downloads it
+pub fn oversize_page_html(url: &str, limit: u64, download_key: Option<&str>) -> String {
+    let url = escape_html(url);
+    let hint = match download_key {
+        Some(key) => format!(
+            "Select the link below and press <b>{}</b> to download the raw content instead.",
+            escape_html(key)
+        ),
+        None => String::from(
+            "Type <b>:download</b> and the address below to download the raw content instead.",
+        ),
+    };
+    format!(
+        "<h1>Page Too Large</h1><hr>\
+         <p>Page exceeds size limit ({}): <b>{url}</b></p>\
+         <p>{hint}</p>\
+         <p><a href=\"{url}\">{url}</a></p>",
+        crate::ui::format_bytes(limit)
+    )
+}
+
+/// Read a response body, giving up as soon as it grows past `limit` bytes.
+/// `Ok(None)` means the limit was hit; chunked bodies without a length are covered too.
+pub async fn read_body_limited(
+    resp: reqwest::Response,
+    limit: u64,
+) -> Result<Option<Vec<u8>>, reqwest::Error> {
+    use futures_util::StreamExt;
+
+    let mut body = Vec::new();
+    let mut stream = resp.bytes_stream();
+    while let Some(chunk) = stream.next().await {
+        let chunk = chunk?;
+        if body.len() as u64 + chunk.len() as u64 > limit {
+            return Ok(None);
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(Some(body))
+}
+
+/// Value of a `charset=` parameter in a Content-Type style string
+fn charset_param(value: &str) -> Option<&str> {
+    value.split(';').find_map(|param| {
+        let (key, value) = param.trim().split_once('=')?;
+        key.trim()
+            .eq_ignore_ascii_case("charset")
+            .then(|| value.trim().trim_matches(|c| c == '"' || c == '\''))
+    })
+}
+
+/// Charset declared by `<meta charset>` or `<meta http-equiv content="...; charset=">`
+/// within the first KiB of the document, as browsers prescan for it
+fn sniff_meta_charset(bytes: &[u8]) -> Option<&'static encoding_rs::Encoding> {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(1024)]).to_ascii_lowercase();
+    let mut rest = head.as_str();
+    while let Some(start) = rest.find("<meta") {
+        let tag = &rest[start..];
+        let tag = &tag[..tag.find('>').unwrap_or(tag.len())];
+        if let Some(pos) = tag.find("charset=") {
+            let label: String = tag[pos + "charset=".len()..]
+                .trim_start_matches(['"', '\''])
+                .chars()
+                .take_while(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | ':' | '.'))
+                .collect();
+            if let Some(encoding) = encoding_rs::Encoding::for_label(label.as_bytes()) {
+                // A meta tag readable as ASCII can't really be UTF-16
+                return Some(encoding.output_encoding());
+            }
+        }
+        rest = &rest[start + "<meta".len()..];
+    }
+    None
+}
+
+/// Decode a body, preferring a BOM, then the Content-Type charset, then a `<meta>` charset,
+/// and finally UTF-8. Invalid sequences become U+FFFD so a page always renders.
+pub fn decode_body(bytes: &[u8], content_type: Option<&str>) -> String {
+    let encoding = content_type
+        .and_then(charset_param)
+        .and_then(|label| encoding_rs::Encoding::for_label(label.as_bytes()))
+        .or_else(|| sniff_meta_charset(bytes))
+        .unwrap_or(encoding_rs::UTF_8);
+    // `decode` also honours a byte order mark over the chosen encoding
+    let (text, _, _) = encoding.decode(bytes);
+    text.into_owned()
+}
+
+/// Extract the suggested filename from a `Content-Disposition` header.
+/// The RFC 5987 `filename*=` form wins over plain `filename=`; the result is not yet sanitized.
+pub fn content_disposition_filename(header: &str) -> Option<String> {
+    let mut plain = None;
+    for param in header.split(';').map(str::trim) {
+        let Some((key, value)) = param.split_once('=') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim().to_ascii_lowercase().as_str() {
+            "filename*" => {
+                // charset'language'percent-encoded-name
+                let encoded = value.splitn(3, '\'').nth(2).unwrap_or(value);
+                let decoded = percent_decode(encoded);
+                if !decoded.is_empty() {
+                    return Some(decoded);
+                }
+            }
+            "filename" => {
+                let unquoted = value.trim_matches('"');
+                if !unquoted.is_empty() {
+                    plain = Some(unquoted.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    plain
+}
+
+fn percent_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && let Some(byte) = input
+                .get(i + 1..i + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        {
+            out.push(byte);
+            i += 3;
+            continue;
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+/// First free path of the form `name (1).ext`, `name (2).ext`, ... next to `path`
+pub fn unique_download_path(path: &Path) -> PathBuf {
+    if !path.exists() {
+        return path.to_path_buf();
+    }
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("download");
+    let ext = path.extension().and_then(|e| e.to_str());
+    (1..)
+        .map(|n| {
+            let name = match ext {
+                Some(ext) => format!("{} ({}).{}", stem, n, ext),
+                None => format!("{} ({})", stem, n),
+            };
+            path.with_file_name(name)
+        })
+        .find(|candidate| !candidate.exists())
+        .unwrap_or_else(|| path.to_path_buf())
 }
 
 /// Detects file extensions based on magic bytes (file signatures)
@@ -1,9 +1,192 @@
-use crate::constants::UI_BORDER_WIDTH;
+use crate::constants::{
+    CODE_WRAP_MARKER, DEFAULT_TAB_STOP, FORM_FIELD_WIDTH, MAX_LINK_REGIONS, TABLE_COLUMN_GAP,
+    UI_BORDER_WIDTH,
+};
+use crate::models::{Form, FormField, InputRegion};
 use ratatui::style::{Color, Modifier, Style};
 use ratatui::text::{Line, Span};
-use scraper::{Html, Node};
+use scraper::{ElementRef, Html, Node, Selector};
+use std::sync::OnceLock;
 use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
+/// Tokenizer state carried across lines while highlighting HTML source
+#[derive(Clone, Copy, PartialEq)]
+enum SourceState {
+    Text,
+    TagName,
+    Attributes,
+    AfterEquals,
+    QuotedValue(char),
+    UnquotedValue,
+    Comment,
+}
+
+/// Highlight raw HTML for the source view, producing exactly one `Line` per source line
+pub fn highlight_html_source(source: &str) -> Vec<Line<'static>> {
+    let tag_style = Style::default().fg(Color::LightBlue);
+    let attr_name_style = Style::default().fg(Color::Yellow);
+    let attr_value_style = Style::default().fg(Color::Green);
+    let comment_style = Style::default().fg(Color::DarkGray);
+    let text_style = Style::default();
+
+    let mut state = SourceState::Text;
+    let mut lines = Vec::new();
+
+    for raw_line in source.lines() {
+        let mut spans: Vec<Span<'static>> = Vec::new();
+        let mut token = String::new();
+        let mut token_style = text_style;
+
+        let chars: Vec<char> = raw_line.chars().collect();
+        for (idx, &ch) in chars.iter().enumerate() {
+            let style = match state {
+                SourceState::Text => {
+                    if ch == '<' {
+                        let rest: String = chars[idx..].iter().take(4).collect();
+                        if rest == "<!--" {
+                            state = SourceState::Comment;
+                            comment_style
+                        } else {
+                            state = SourceState::TagName;
+                            tag_style
+                        }
+                    } else {
+                        text_style
+                    }
+                }
+                SourceState::TagName => {
+                    if ch == '>' {
+                        state = SourceState::Text;
+                        tag_style
+                    } else if ch.is_whitespace() {
+                        state = SourceState::Attributes;
+                        text_style
+                    } else {
+                        tag_style
+                    }
+                }
+                SourceState::Attributes => match ch {
+                    '>' => {
+                        state = SourceState::Text;
+                        tag_style
+                    }
+                    '/' => tag_style,
+                    '=' => {
+                        state = SourceState::AfterEquals;
+                        text_style
+                    }
+                    c if c.is_whitespace() => text_style,
+                    _ => attr_name_style,
+                },
+                SourceState::AfterEquals => match ch {
+                    '"' | '\'' => {
+                        state = SourceState::QuotedValue(ch);
+                        attr_value_style
+                    }
+                    '>' => {
+                        state = SourceState::Text;
+                        tag_style
+                    }
+                    c if c.is_whitespace() => text_style,
+                    _ => {
+                        state = SourceState::UnquotedValue;
+                        attr_value_style
+                    }
+                },
+                SourceState::QuotedValue(quote) => {
+                    if ch == quote {
+                        state = SourceState::Attributes;
+                    }
+                    attr_value_style
+                }
+                SourceState::UnquotedValue => {
+                    if ch == '>' {
+                        state = SourceState::Text;
+                        tag_style
+                    } else if ch.is_whitespace() {
+                        state = SourceState::Attributes;
+                        text_style
+                    } else {
+                        attr_value_style
+                    }
+                }
+                SourceState::Comment => {
+                    if ch == '>' && idx >= 2 && chars[idx - 2] == '-' && chars[idx - 1] == '-' {
+                        state = SourceState::Text;
+                    }
+                    comment_style
+                }
+            };
+
+            // Merge runs of identically styled characters into a single span
+            if style != token_style && !token.is_empty() {
+                spans.push(Span::styled(std::mem::take(&mut token), token_style));
+            }
+            token_style = style;
+            token.push(ch);
+        }
+
+        if !token.is_empty() {
+            spans.push(Span::styled(token, token_style));
+        }
+        lines.push(Line::from(spans));
+    }
+
+    lines
+}
+
+/// Prefix each source line with a dim, right-aligned 1-based line number
+pub fn add_line_numbers(lines: Vec<Line<'static>>) -> Vec<Line<'static>> {
+    let gutter_width = lines.len().to_string().len();
+    let gutter_style = Style::default().fg(Color::DarkGray);
+
+    lines
+        .into_iter()
+        .enumerate()
+        .map(|(idx, mut line)| {
+            let number = format!("{:>width$} ", idx + 1, width = gutter_width);
+            line.spans.insert(0, Span::styled(number, gutter_style));
+            line
+        })
+        .collect()
+}
+
+/// Soft-wrap lines wider than `width` columns. Continuation rows are indented by `indent`
+/// columns so they line up after a gutter such as the source view's line numbers.
+pub fn wrap_lines(lines: Vec<Line<'static>>, width: usize, indent: usize) -> Vec<Line<'static>> {
+    let width = width.max(indent + 1);
+    let mut wrapped = Vec::with_capacity(lines.len());
+    for line in lines {
+        if line.width() <= width {
+            wrapped.push(line);
+            continue;
+        }
+        let mut row: Vec<Span<'static>> = Vec::new();
+        let mut row_width = 0;
+        for span in line.spans {
+            let mut chunk = String::new();
+            for ch in span.content.chars() {
+                let ch_width = UnicodeWidthChar::width(ch).unwrap_or(0);
+                if row_width + ch_width > width {
+                    if !chunk.is_empty() {
+                        row.push(Span::styled(std::mem::take(&mut chunk), span.style));
+                    }
+                    wrapped.push(Line::from(std::mem::take(&mut row)));
+                    row.push(Span::raw(" ".repeat(indent)));
+                    row_width = indent;
+                }
+                chunk.push(ch);
+                row_width += ch_width;
+            }
+            if !chunk.is_empty() {
+                row.push(Span::styled(chunk, span.style));
+            }
+        }
+        wrapped.push(Line::from(row));
+    }
+    wrapped
+}
+
 pub struct DomRenderer {
     pub lines: Vec<Line<'static>>,
     current_line: Vec<Span<'static>>,
@@ -11,9 +194,29 @@ pub struct DomRenderer {
     pub links: Vec<crate::models::LinkRegion>,
     max_width: usize,
     current_line_width: usize,
+    /// A block ended and the next line of content should start after a blank one
+    blank_pending: bool,
     active_link_url: Option<String>,
+    /// Whether the active link is `rel="noreferrer"`
+    active_link_no_referrer: bool,
     preserve_whitespace: bool,
     list_depth: usize,
+    tab_width: usize,
+    quote_depth: usize,
+    suppress_next_space: bool,
+    max_links: usize,
+    pub forms: Vec<Form>,
+    pub inputs: Vec<InputRegion>,
+    current_form: Option<usize>,
+    prefill: Vec<Form>,
+    wrap: bool,
+    skip_boilerplate: bool,
+    /// Width of the indentation continuation rows of the current preformatted line start with
+    code_indent: usize,
+    /// Headings being rendered; links inside them are tinted to stand out from the heading
+    heading_depth: usize,
+    /// Open sections as (heading level, number) when headings are numbered, outermost first
+    heading_numbers: Option<Vec<(usize, usize)>>,
 }
 
 impl DomRenderer {
@@ -42,12 +245,216 @@ impl DomRenderer {
             links: Vec::new(),
             max_width: width.saturating_sub(UI_BORDER_WIDTH),
             current_line_width: 0,
+            blank_pending: false,
             active_link_url: None,
+            active_link_no_referrer: false,
             preserve_whitespace: false,
             list_depth: 0,
+            tab_width: DEFAULT_TAB_STOP,
+            quote_depth: 0,
+            suppress_next_space: false,
+            max_links: MAX_LINK_REGIONS,
+            forms: Vec::new(),
+            inputs: Vec::new(),
+            current_form: None,
+            prefill: Vec::new(),
+            wrap: true,
+            skip_boilerplate: false,
+            code_indent: 0,
+            heading_depth: 0,
+            heading_numbers: None,
         }
     }
 
+    /// Quote mark for the current `<q>` nesting level, alternating double and single
+    fn quote_mark(&self) -> &'static str {
+        if self.quote_depth.is_multiple_of(2) {
+            "\""
+        } else {
+            "'"
+        }
+    }
+
+    /// Push a quotation mark in a slightly dimmed version of the current style
+    fn push_quote_mark(&mut self, mark: &str) {
+        let new_style = self.current_style().add_modifier(Modifier::DIM);
+        self.push_style(new_style);
+        self.push_word(mark);
+        self.pop_style();
+    }
+
+    /// Limit how many link regions are recorded; links past the cap still render styled
+    pub fn set_max_links(&mut self, max: usize) {
+        self.max_links = max;
+    }
+
+    /// With wrapping off, text only breaks where the markup does and long lines run off screen
+    pub fn set_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+    }
+
+    /// Prefix headings with their outline number (1, 1.1, 1.2, 2, ...)
+    pub fn set_heading_numbers(&mut self, number: bool) {
+        self.heading_numbers = number.then(Vec::new);
+    }
+
+    /// Outline number for the next heading of `level` (1 for `h1`), when headings are numbered.
+    /// A heading that skips levels still opens a single subsection, and one that closes deeper
+    /// sections without a sibling to follow continues after the last of them.
+    fn next_heading_number(&mut self, level: usize) -> Option<String> {
+        let numbers = self.heading_numbers.as_mut()?;
+        let mut closed = None;
+        while let Some(&(open_level, number)) = numbers.last()
+            && open_level > level
+        {
+            closed = Some(number);
+            numbers.pop();
+        }
+        match numbers.last_mut() {
+            Some((open_level, number)) if *open_level == level => *number += 1,
+            _ => numbers.push((level, closed.map_or(1, |number| number + 1))),
+        }
+        let parts: Vec<String> = numbers.iter().map(|(_, number)| number.to_string()).collect();
+        Some(parts.join("."))
+    }
+
+    /// Keep values typed into the forms of a previous render of the same page
+    pub fn set_form_values(&mut self, forms: Vec<Form>) {
+        self.prefill = forms;
+    }
+
+    /// Push a bracketed stand-in for something that can't be shown or used as-is.
+    /// It's separated like a word, but the space stays out of any link region.
+    fn push_placeholder(&mut self, text: &str) {
+        if self.suppress_next_space {
+            self.suppress_next_space = false;
+        } else if self.current_line_width > 0 && !self.current_line.is_empty() {
+            let link = self.active_link_url.take();
+            self.push_word(" ");
+            self.active_link_url = link;
+        }
+        // Inside a link the placeholder keeps the link colour so it reads as one
+        if self.active_link_url.is_none() {
+            let new_style = self.current_style().fg(Color::DarkGray);
+            self.push_style(new_style);
+            self.push_word(text);
+            self.pop_style();
+        } else {
+            self.push_word(text);
+        }
+    }
+
+    /// Buttons need scripts this browser can't run, so they show as `[button: label]` to make
+    /// clear something interactive is there, with any `aria-expanded` state it reports
+    fn render_button(
+        &mut self,
+        node: ego_tree::NodeRef<scraper::node::Node>,
+        elem: &scraper::node::Element,
+    ) {
+        let text = ElementRef::wrap(node)
+            .map(|element| element.text().flat_map(str::split_whitespace).collect::<Vec<_>>())
+            .unwrap_or_default()
+            .join(" ");
+        let named = [elem.attr("aria-label"), elem.attr("title"), elem.attr("value")];
+        let label = std::iter::once(Some(text.as_str()))
+            .chain(named)
+            .flatten()
+            .map(str::trim)
+            .find(|label| !label.is_empty())
+            .unwrap_or("unlabelled");
+        let state = match elem.attr("aria-expanded") {
+            Some("true") => ", expanded",
+            Some("false") => ", collapsed",
+            _ => "",
+        };
+        self.push_placeholder(&format!("[button: {}{}]", label, state));
+    }
+
+    /// Draw an `<input>` and register it with the enclosing form.
+    /// Text-like inputs become editable `[value____]` boxes; hidden ones are only submitted.
+    fn render_input(&mut self, elem: &scraper::node::Element) {
+        let kind = elem.attr("type").unwrap_or("text").to_ascii_lowercase();
+        let editable = matches!(kind.as_str(), "text" | "search" | "email" | "url" | "tel");
+        if !editable && kind != "hidden" {
+            return;
+        }
+
+        let name = elem.attr("name").unwrap_or("").to_string();
+        let field_index = self
+            .current_form
+            .map(|form_index| self.forms[form_index].fields.len());
+        let value = match (self.current_form, field_index) {
+            (Some(form_index), Some(field_index)) => self
+                .prefill
+                .get(form_index)
+                .and_then(|form| form.fields.get(field_index))
+                .filter(|field| field.name == name)
+                .map(|field| field.value.clone()),
+            _ => None,
+        }
+        .unwrap_or_else(|| elem.attr("value").unwrap_or("").to_string());
+
+        if let Some(form_index) = self.current_form {
+            self.forms[form_index].fields.push(FormField {
+                name,
+                value: value.clone(),
+                editable,
+            });
+        }
+        if !editable {
+            return;
+        }
+
+        let (text, style) = if value.is_empty() {
+            let placeholder = elem.attr("placeholder").unwrap_or("");
+            (placeholder, self.current_style().fg(Color::DarkGray))
+        } else {
+            (value.as_str(), self.current_style().fg(Color::Yellow))
+        };
+        let width = FORM_FIELD_WIDTH.min(self.max_width.saturating_sub(2)).max(1);
+        let boxed = format!("[{}]", fit_field_text(text, width));
+
+        if self.current_line_width > 0 {
+            self.push_word(" ");
+        }
+        self.push_style(style);
+        self.push_word(&boxed);
+        self.pop_style();
+
+        if let (Some(form_index), Some(field_index)) = (self.current_form, field_index) {
+            let x_end = self.current_line_width;
+            self.inputs.push(InputRegion {
+                form_index,
+                field_index,
+                line_index: self.lines.len(),
+                x_start: x_end.saturating_sub(UnicodeWidthStr::width(boxed.as_str())),
+                x_end,
+            });
+        }
+    }
+
+    /// Set the tab stop used when expanding tabs in preformatted text
+    pub fn set_tab_width(&mut self, width: usize) {
+        self.tab_width = width.max(1);
+    }
+
+    /// Replace tabs with spaces up to the next tab stop, starting from `start_col`
+    fn expand_tabs(&self, text: &str, start_col: usize) -> String {
+        let mut expanded = String::with_capacity(text.len());
+        let mut col = start_col;
+        for ch in text.chars() {
+            if ch == '\t' {
+                let spaces = self.tab_width - (col % self.tab_width);
+                expanded.push_str(&" ".repeat(spaces));
+                col += spaces;
+            } else {
+                expanded.push(ch);
+                col += UnicodeWidthChar::width(ch).unwrap_or(0);
+            }
+        }
+        expanded
+    }
+
     pub fn render(&mut self, document: &Html) {
         for node in document.tree.root().children() {
             self.walk(node);
@@ -55,6 +462,21 @@ impl DomRenderer {
         self.flush_line();
     }
 
+    /// Reader mode: render only the page's main content, leaving out navigation, sidebars,
+    /// headers and footers. Falls back to the whole page without those when nothing stands out.
+    pub fn render_reader(&mut self, document: &Html) {
+        self.skip_boilerplate = true;
+        match main_content(document) {
+            Some(content) => self.walk(*content),
+            None => {
+                for node in document.tree.root().children() {
+                    self.walk(node);
+                }
+            }
+        }
+        self.flush_line();
+    }
+
     fn flush_line(&mut self) {
         if !self.current_line.is_empty() {
             self.lines.push(Line::from(self.current_line.clone()));
@@ -63,23 +485,30 @@ impl DomRenderer {
         }
     }
 
+    /// Separate what follows from the block before by one blank line. The blank is only added
+    /// once the next line starts, so adjacent block boundaries (`</p><p>`, `</ul><p>`, ...)
+    /// share it and nothing trails the last block.
     fn add_vertical_space(&mut self) {
-        // Only flush the current line if it has content
-        if !self.current_line.is_empty() {
-            self.flush_line();
-        }
+        self.flush_line();
+        self.blank_pending = true;
+    }
 
-        // Add vertical space only if the last line has content
-        if let Some(last) = self.lines.last() {
-            if !last.spans.is_empty() {
-                self.lines.push(Line::from(""));
-            }
+    /// Add the blank line a block boundary asked for, unless the page hasn't started or one is
+    /// already there
+    fn place_pending_blank(&mut self) {
+        if std::mem::take(&mut self.blank_pending)
+            && self.lines.last().is_some_and(|last| !last.spans.is_empty())
+        {
+            self.lines.push(Line::default());
         }
     }
 
     /// Internal helper to push a span to the current line and track its link region
     /// Now breaks content into individual characters for precise visual mode selection
     fn push_span_to_line(&mut self, content: String) {
+        if self.current_line.is_empty() {
+            self.place_pending_blank();
+        }
         let chars: Vec<char> = content.chars().collect();
 
         for ch in chars {
@@ -98,18 +527,28 @@ impl DomRenderer {
 
                 // Try to merge with the previous link region if it's on the same line and contiguous
                 if let Some(last) = self.links.last_mut() {
-                    if last.line_index == line_idx && last.url == *url && last.x_end == start_x {
+                    if last.line_index == line_idx
+                        && last.url == *url
+                        && last.no_referrer == self.active_link_no_referrer
+                        && last.x_end == start_x
+                    {
                         last.x_end = end_x;
                         continue; // Continue to next character instead of creating new region
                     }
                 }
 
+                // Past the cap the text stays link-styled but isn't navigable
+                if self.links.len() >= self.max_links {
+                    continue;
+                }
+
                 // Create a new link region for this character
                 self.links.push(crate::models::LinkRegion {
                     url: url.clone(),
                     line_index: line_idx,
                     x_start: start_x,
                     x_end: end_x,
+                    no_referrer: self.active_link_no_referrer,
                 });
             }
         }
@@ -128,14 +567,24 @@ impl DomRenderer {
         if word == " " && self.current_line_width == 0 && !self.preserve_whitespace {
             return;
         }
+        // A separating space that doesn't fit ends the line rather than starting the next one,
+        // which would leave the line after a word that exactly fills the width indented by one
+        if word == " "
+            && self.wrap
+            && !self.preserve_whitespace
+            && self.current_line_width >= self.max_width
+        {
+            self.flush_line();
+            return;
+        }
 
         // Ensure indentation is applied at the start of any word on a new line
         self.apply_indentation();
 
         let word_width = UnicodeWidthStr::width(word);
 
-        // Case 1: Word fits on the current line
-        if self.current_line_width + word_width <= self.max_width {
+        // Case 1: Word fits on the current line (always, when not wrapping)
+        if !self.wrap || self.current_line_width + word_width <= self.max_width {
             self.push_span_to_line(word.to_string());
         }
         // Case 2: Word fits on a new line (Standard Wrap)
@@ -188,17 +637,105 @@ impl DomRenderer {
         }
     }
 
+    /// Preformatted text keeps its spacing. A line too wide for the viewport soft-wraps onto
+    /// continuation rows indented like the line itself and marked with `CODE_WRAP_MARKER`.
+    fn push_preformatted(&mut self, text: &str) {
+        if self.current_line_width == 0 {
+            // A new source line; highlighted code may arrive in several text nodes per line
+            self.apply_indentation();
+            let leading = text.chars().take_while(|&c| c == ' ').count();
+            self.code_indent = (self.current_line_width + leading).min(self.max_width / 2);
+        }
+        if !self.wrap {
+            self.push_span_to_line(text.to_string());
+            return;
+        }
+
+        let prefix_width = self.code_indent + UnicodeWidthStr::width(CODE_WRAP_MARKER);
+        let mut chunk_start = 0;
+        let mut width = self.current_line_width;
+        for (idx, ch) in text.char_indices() {
+            let ch_width = UnicodeWidthChar::width(ch).unwrap_or(0);
+            // A row holding nothing but the continuation prefix takes the character anyway
+            if width + ch_width > self.max_width && width > prefix_width {
+                self.push_span_to_line(text[chunk_start..idx].to_string());
+                self.start_code_continuation();
+                chunk_start = idx;
+                width = self.current_line_width;
+            }
+            width += ch_width;
+        }
+        self.push_span_to_line(text[chunk_start..].to_string());
+    }
+
+    fn start_code_continuation(&mut self) {
+        self.flush_line();
+        // The prefix is layout, not part of a link that happens to wrap
+        let link = self.active_link_url.take();
+        self.push_span_to_line(" ".repeat(self.code_indent));
+        self.push_style(Style::default().fg(Color::DarkGray));
+        self.push_span_to_line(CODE_WRAP_MARKER.to_string());
+        self.pop_style();
+        self.active_link_url = link;
+    }
+
+    /// `<noscript>` holds the page meant for agents without JavaScript, like this one.
+    /// The parser keeps its content as raw (sometimes entity-escaped) text, so it's parsed again
+    /// and rendered as real markup.
+    fn render_noscript(&mut self, node: ego_tree::NodeRef<scraper::node::Node>) {
+        let raw: String = node
+            .children()
+            .filter_map(|child| child.value().as_text().map(|text| text.to_string()))
+            .collect();
+        let has_elements = node.children().any(|child| child.value().is_element());
+        if has_elements || !(raw.contains('<') || raw.contains("&lt;")) {
+            for child in node.children() {
+                self.walk(child);
+            }
+            return;
+        }
+
+        let markup = if raw.contains('<') {
+            raw
+        } else {
+            unescape_markup(&raw)
+        };
+        let fragment = Html::parse_fragment(&markup);
+        for child in fragment.tree.root().children() {
+            self.walk(child);
+        }
+    }
+
     fn walk(&mut self, node: ego_tree::NodeRef<scraper::node::Node>) {
         match node.value() {
             Node::Text(text) => {
                 if self.preserve_whitespace {
-                    for line in text.text.lines() {
-                        self.push_word(line);
-                        self.flush_line();
+                    for (idx, line) in text.text.split('\n').enumerate() {
+                        // Each newline ends the current line; an empty one is a deliberate blank
+                        if idx > 0 {
+                            if self.current_line.is_empty() {
+                                // A blank owed to the block before counts as this one
+                                let owed = self.blank_pending;
+                                self.place_pending_blank();
+                                if !owed || self.lines.last().is_none() {
+                                    self.lines.push(Line::default());
+                                }
+                            } else {
+                                self.flush_line();
+                            }
+                        }
+                        let line = line.strip_suffix('\r').unwrap_or(line);
+                        if !line.is_empty() {
+                            let expanded = self.expand_tabs(line, self.current_line_width);
+                            self.push_preformatted(&expanded);
+                        }
                     }
                 } else {
                     for word in text.text.split_whitespace() {
-                        if self.current_line_width > 0 && !self.current_line.is_empty() {
+                        if self.suppress_next_space {
+                            // Hug the opening quote mark
+                            self.suppress_next_space = false;
+                        } else if self.current_line_width > 0 && !self.current_line.is_empty() {
                             // Add a space between words if we aren't at the start of a line
                             self.push_word(" ");
                         }
@@ -221,9 +758,25 @@ impl DomRenderer {
                 if elem.attr("hidden").is_some() || elem.attr("aria-hidden") == Some("true") {
                     return;
                 }
+                if self.skip_boilerplate && matches!(tag, "nav" | "aside" | "header" | "footer") {
+                    return;
+                }
+                if tag == "noscript" {
+                    self.render_noscript(node);
+                    return;
+                }
+                // Links styled as buttons still work as links, and so do links inside one
+                let is_button =
+                    tag == "button" || (elem.attr("role") == Some("button") && tag != "a");
+                if is_button && !has_usable_descendant(node) {
+                    self.render_button(node, elem);
+                    return;
+                }
 
                 let old_link = self.active_link_url.clone();
+                let old_no_referrer = self.active_link_no_referrer;
                 let old_preserve = self.preserve_whitespace;
+                let old_form = self.current_form;
 
                 match tag {
                     "b" | "strong" => {
@@ -234,23 +787,46 @@ impl DomRenderer {
                         let new_style = self.current_style().add_modifier(Modifier::ITALIC);
                         self.push_style(new_style);
                     }
+                    "u" | "ins" => {
+                        let new_style = self.current_style().add_modifier(Modifier::UNDERLINED);
+                        self.push_style(new_style);
+                    }
+                    "s" | "del" => {
+                        let new_style = self.current_style().add_modifier(Modifier::CROSSED_OUT);
+                        self.push_style(new_style);
+                    }
                     "a" => {
+                        // Other modifiers carry over; a heading's links get a lighter tint
+                        let link_color = if self.heading_depth > 0 {
+                            Color::LightCyan
+                        } else {
+                            Color::Cyan
+                        };
                         let new_style = self
                             .current_style()
-                            .fg(Color::Cyan)
+                            .fg(link_color)
                             .add_modifier(Modifier::UNDERLINED);
                         self.push_style(new_style);
                         if let Some(href) = elem.attr("href") {
                             self.active_link_url = Some(href.to_string());
+                            self.active_link_no_referrer = elem.attr("rel").is_some_and(|rel| {
+                                rel.split_ascii_whitespace()
+                                    .any(|r| r.eq_ignore_ascii_case("noreferrer"))
+                            });
                         }
                     }
                     "h1" | "h2" | "h3" => {
                         self.add_vertical_space();
+                        self.heading_depth += 1;
                         let new_style = self
                             .current_style()
                             .fg(Color::White)
                             .add_modifier(Modifier::BOLD);
                         self.push_style(new_style);
+                        let level = tag[1..].parse().unwrap_or(1);
+                        if let Some(number) = self.next_heading_number(level) {
+                            self.push_word(&number);
+                        }
                     }
                     "pre" | "code" => {
                         self.flush_line();
@@ -269,17 +845,76 @@ impl DomRenderer {
                         self.push_word(&bullet);
                     }
                     "img" => {
-                        let alt = elem.attr("alt").unwrap_or("IMAGE");
-                        let new_style = self.current_style().fg(Color::DarkGray);
+                        let label = [elem.attr("alt"), elem.attr("title")]
+                            .into_iter()
+                            .flatten()
+                            .map(str::trim)
+                            .find(|text| !text.is_empty())
+                            .unwrap_or("IMAGE");
+                        self.push_placeholder(&format!("[{}]", label));
+                    }
+                    "q" => {
+                        if self.current_line_width > 0 {
+                            self.push_word(" ");
+                        }
+                        let mark = self.quote_mark();
+                        self.push_quote_mark(mark);
+                        self.quote_depth += 1;
+                        self.suppress_next_space = true;
+                    }
+                    "summary" => {
+                        self.flush_line();
+                        let open = node
+                            .parent()
+                            .and_then(|parent| parent.value().as_element().map(|e| e.attr("open")))
+                            .is_some_and(|open| open.is_some());
+                        // The summary text brings its own separating space
+                        self.push_word(if open { "▾" } else { "▸" });
+                        let new_style = self.current_style().add_modifier(Modifier::BOLD);
                         self.push_style(new_style);
-                        self.push_word(&format!("[{}] ", alt));
-                        self.pop_style();
                     }
+                    "figure" | "dl" => self.add_vertical_space(),
+                    "dt" => {
+                        self.flush_line();
+                        let new_style = self.current_style().add_modifier(Modifier::BOLD);
+                        self.push_style(new_style);
+                    }
+                    "dd" => {
+                        self.flush_line();
+                        self.list_depth += 1;
+                    }
+                    "figcaption" => {
+                        self.flush_line();
+                        let new_style = self
+                            .current_style()
+                            .fg(Color::DarkGray)
+                            .add_modifier(Modifier::ITALIC);
+                        self.push_style(new_style);
+                    }
+                    "address" => {
+                        self.flush_line();
+                        let new_style = self.current_style().add_modifier(Modifier::ITALIC);
+                        self.push_style(new_style);
+                    }
+                    "time" => {
+                        let new_style = self.current_style().add_modifier(Modifier::DIM);
+                        self.push_style(new_style);
+                    }
+                    "form" => {
+                        self.flush_line();
+                        self.forms.push(Form {
+                            action: elem.attr("action").unwrap_or("").to_string(),
+                            method: elem.attr("method").unwrap_or("get").to_ascii_lowercase(),
+                            fields: Vec::new(),
+                        });
+                        self.current_form = Some(self.forms.len() - 1);
+                    }
+                    "input" => self.render_input(elem),
                     "br" => self.flush_line(),
                     "p" | "main" | "article" | "section" | "table" | "aside" => {
                         self.add_vertical_space()
                     }
-                    "div" | "header" | "footer" | "nav" | "tr" => self.flush_line(),
+                    "div" | "header" | "footer" | "nav" | "tr" | "details" => self.flush_line(),
                     "td" | "th" => self.push_word("  "),
                     "hr" => {
                         self.add_vertical_space();
@@ -289,30 +924,65 @@ impl DomRenderer {
                     _ => {}
                 }
 
+                if is_button {
+                    self.push_placeholder("[button]");
+                }
                 for child in node.children() {
                     self.walk(child);
                 }
 
+                // A machine-readable date that says more than the text follows it, still dimmed
+                if tag == "time"
+                    && let Some(datetime) = elem.attr("datetime").map(str::trim)
+                    && !datetime.is_empty()
+                {
+                    let text = ElementRef::wrap(node)
+                        .map(|e| e.text().flat_map(str::split_whitespace).collect::<Vec<_>>())
+                        .unwrap_or_default()
+                        .join(" ");
+                    if text != datetime {
+                        if self.current_line_width > 0 {
+                            self.push_word(" ");
+                        }
+                        self.push_word(&format!("({})", datetime));
+                    }
+                }
+
                 // Pop style from stack for tags that push styles
                 match tag {
-                    "b" | "strong" | "i" | "em" | "a" | "h1" | "h2" | "h3" | "pre" | "code" => {
+                    "b" | "strong" | "i" | "em" | "u" | "ins" | "s" | "del" | "a" | "h1" | "h2"
+                    | "h3" | "pre" | "code" | "figcaption" | "dt" | "summary" | "address"
+                    | "time" => {
                         self.pop_style();
                     }
                     _ => {}
                 }
+                if matches!(tag, "h1" | "h2" | "h3") {
+                    self.heading_depth = self.heading_depth.saturating_sub(1);
+                }
+
+                if tag == "q" {
+                    self.quote_depth = self.quote_depth.saturating_sub(1);
+                    self.suppress_next_space = false;
+                    let mark = self.quote_mark();
+                    self.push_quote_mark(mark);
+                }
 
                 // Restore other state
                 self.active_link_url = old_link;
+                self.active_link_no_referrer = old_no_referrer;
                 self.preserve_whitespace = old_preserve;
+                self.current_form = old_form;
 
                 match tag {
-                    "ul" | "ol" => {
+                    "ul" | "ol" | "dd" => {
                         self.list_depth = self.list_depth.saturating_sub(1);
                         self.flush_line();
                     }
                     "h1" | "h2" | "h3" | "p" | "main" | "article" | "section" | "table"
-                    | "aside" | "pre" => self.add_vertical_space(),
-                    "div" | "li" | "header" | "footer" | "nav" | "tr" => self.flush_line(),
+                    | "aside" | "pre" | "figure" | "dl" => self.add_vertical_space(),
+                    "div" | "li" | "header" | "footer" | "nav" | "tr" | "figcaption" | "dt"
+                    | "form" | "details" | "summary" | "address" => self.flush_line(),
                     _ => {}
                 }
             }
@@ -320,3 +990,176 @@ impl DomRenderer {
         }
     }
 }
+
+/// Whether anything inside `node` works without scripts: a link or a form control
+fn has_usable_descendant(node: ego_tree::NodeRef<scraper::node::Node>) -> bool {
+    node.descendants().skip(1).any(|child| {
+        child.value().as_element().is_some_and(|e| match e.name() {
+            "a" => e.attr("href").is_some(),
+            "input" | "select" | "textarea" | "button" => true,
+            _ => false,
+        })
+    })
+}
+
+/// The element holding a page's article, for reader mode: the `<article>` with the most text,
+/// else `<main>`, else the element whose direct `<p>` children hold the most text
+pub fn main_content(document: &Html) -> Option<ElementRef<'_>> {
+    static ARTICLE_SELECTOR: OnceLock<Selector> = OnceLock::new();
+    static MAIN_SELECTOR: OnceLock<Selector> = OnceLock::new();
+    static PARAGRAPH_SELECTOR: OnceLock<Selector> = OnceLock::new();
+    let article_selector = ARTICLE_SELECTOR.get_or_init(|| Selector::parse("article").unwrap());
+    let main_selector =
+        MAIN_SELECTOR.get_or_init(|| Selector::parse("main, [role=\"main\"]").unwrap());
+    let paragraph_selector = PARAGRAPH_SELECTOR.get_or_init(|| Selector::parse("p").unwrap());
+
+    let text_len = |element: &ElementRef| -> usize {
+        element.text().map(|text| text.trim().len()).sum()
+    };
+
+    if let Some(article) = document.select(article_selector).max_by_key(text_len) {
+        return Some(article);
+    }
+    if let Some(main) = document.select(main_selector).next() {
+        return Some(main);
+    }
+
+    // Densest container: score each parent by the paragraph text directly inside it
+    let mut scores: Vec<(ElementRef, usize)> = Vec::new();
+    for paragraph in document.select(paragraph_selector) {
+        let Some(parent) = paragraph.parent().and_then(ElementRef::wrap) else {
+            continue;
+        };
+        let len = text_len(&paragraph);
+        match scores.iter_mut().find(|(element, _)| element.id() == parent.id()) {
+            Some((_, score)) => *score += len,
+            None => scores.push((parent, len)),
+        }
+    }
+    scores
+        .into_iter()
+        .filter(|(_, score)| *score > 0)
+        .max_by_key(|(_, score)| *score)
+        .map(|(element, _)| element)
+}
+
+/// Render an HTML document to styled lines and link regions without a terminal.
+/// `width` is the full terminal width, as passed to `DomRenderer::new`.
+pub fn render_html_to_lines(
+    html: &str,
+    width: usize,
+) -> (Vec<Line<'static>>, Vec<crate::models::LinkRegion>) {
+    let document = Html::parse_document(html);
+    let mut renderer = DomRenderer::new(width);
+    renderer.render(&document);
+    (renderer.lines, renderer.links)
+}
+
+/// Split CSV/TSV text into rows of fields. Quoted fields may contain the delimiter,
+/// line breaks and doubled (`""`) quotes.
+pub fn parse_delimited(text: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        if in_quotes {
+            if ch != '"' {
+                field.push(ch);
+            } else if chars.peek() == Some(&'"') {
+                chars.next();
+                field.push('"');
+            } else {
+                in_quotes = false;
+            }
+        } else if ch == '"' && field.is_empty() {
+            in_quotes = true;
+        } else if ch == delimiter {
+            row.push(std::mem::take(&mut field));
+        } else if ch == '\n' {
+            row.push(std::mem::take(&mut field));
+            rows.push(std::mem::take(&mut row));
+        } else if ch != '\r' {
+            field.push(ch);
+        }
+    }
+    // The last record may not end with a newline
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+/// Lay rows out in columns padded to their widest cell, with a bold first row when it's a header.
+/// Line breaks inside a cell become spaces so every row stays on one line.
+pub fn table_lines(rows: &[Vec<String>], header: bool) -> Vec<Line<'static>> {
+    let rows: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| row.iter().map(|cell| cell.replace(['\r', '\n'], " ")).collect())
+        .collect();
+
+    let mut widths: Vec<usize> = Vec::new();
+    for row in &rows {
+        for (col, cell) in row.iter().enumerate() {
+            let width = UnicodeWidthStr::width(cell.as_str());
+            match widths.get_mut(col) {
+                Some(max) => *max = (*max).max(width),
+                None => widths.push(width),
+            }
+        }
+    }
+
+    rows.into_iter()
+        .enumerate()
+        .map(|(index, row)| {
+            let style = if header && index == 0 {
+                Style::default().add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            let last = row.len().saturating_sub(1);
+            let spans: Vec<Span<'static>> = row
+                .into_iter()
+                .enumerate()
+                .map(|(col, mut cell)| {
+                    // The last cell isn't padded so lines don't carry trailing blanks
+                    if col < last {
+                        let padding = widths[col] - UnicodeWidthStr::width(cell.as_str());
+                        cell.push_str(&" ".repeat(padding + TABLE_COLUMN_GAP));
+                    }
+                    Span::styled(cell, style)
+                })
+                .collect();
+            Line::from(spans)
+        })
+        .collect()
+}
+
+/// Undo the entity escaping some pages apply to markup inside `<noscript>`
+fn unescape_markup(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Fit field text into `width` columns, keeping the end visible and padding with underscores
+fn fit_field_text(text: &str, width: usize) -> String {
+    let mut kept: Vec<char> = Vec::new();
+    let mut used = 0;
+    for ch in text.chars().rev() {
+        let ch_width = UnicodeWidthChar::width(ch).unwrap_or(0);
+        if used + ch_width > width {
+            break;
+        }
+        used += ch_width;
+        kept.push(ch);
+    }
+    let mut fitted: String = kept.into_iter().rev().collect();
+    fitted.push_str(&"_".repeat(width - used));
+    fitted
+}
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// The open tabs saved on quit and reopened at the next start, as `session.json`
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Serialize)]
+pub struct Session {
+    pub tabs: Vec<SessionTab>,
+    /// Index into `tabs` of the tab that was in front
+    pub active: usize,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct SessionTab {
+    pub url: String,
+    /// The tab's narrowed text column, if it had one
+    #[serde(default)]
+    pub column_width: Option<usize>,
+}
+
+impl Session {
+    /// Read a saved session; a missing or unreadable file gives `None`
+    pub fn load(path: &Path) -> Option<Self> {
+        let text = std::fs::read_to_string(path).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    /// Write the session, creating its directory first. The file is only readable by the
+    /// current user, since saved addresses may carry tokens. It's written next to the old one
+    /// and moved over it, so a crash part way through leaves the previous session intact.
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let text = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        let mut staged = path.as_os_str().to_owned();
+        staged.push(format!(".{}.tmp", std::process::id()));
+        let staged = PathBuf::from(staged);
+        let saved = write_private(&staged, text.as_bytes())
+            .and_then(|()| std::fs::rename(&staged, path));
+        if saved.is_err() {
+            let _ = std::fs::remove_file(&staged);
+        }
+        saved
+    }
+}
+
+/// Create `path` afresh with `contents`, on unix readable and writable only by the current user
+fn write_private(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    use std::io::Write;
+
+    // Left over from a crash, and possibly with looser permissions
+    let _ = std::fs::remove_file(path);
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file.write_all(contents)?;
+    file.sync_all()
+}
@@ -1,6 +1,7 @@
-use crate::app::App;
+use crate::app::{App, BrowserTab};
+use crate::config::DownloadConflictPolicy;
 use crate::constants::{TAB_BAR_HEIGHT, URL_BAR_HEIGHT};
-use crate::models::{InputMode, LinkRegion};
+use crate::models::{Download, DownloadStatus, InputMode, LinkRegion};
 use ratatui::{
     Frame,
     layout::{Constraint, Direction, Layout, Rect},
@@ -9,13 +10,70 @@ use ratatui::{
     widgets::{Block, Borders, Clear, Gauge, Paragraph, Tabs},
 };
 use crate::constants::*;
+use std::borrow::Cow;
+use std::io::{self, Write};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Strip control characters so a page title can't inject terminal escape sequences
+pub fn sanitize_title(title: &str) -> String {
+    title.chars().filter(|c| !c.is_control()).collect()
+}
+
+/// Set the terminal window/tab title using an OSC 0 escape sequence
+pub fn write_terminal_title<W: Write>(writer: &mut W, title: &str) -> io::Result<()> {
+    write!(writer, "\x1b]0;{}\x07", sanitize_title(title))?;
+    writer.flush()
+}
+
+/// Put the terminal into the browser's full-screen mode: raw input, alternate screen, mouse capture
+pub fn enter_tui<W: Write>(writer: &mut W) -> io::Result<()> {
+    crossterm::terminal::enable_raw_mode()?;
+    crossterm::execute!(
+        writer,
+        crossterm::terminal::EnterAlternateScreen,
+        crossterm::event::EnableMouseCapture
+    )
+}
+
+/// Undo `enter_tui`, handing the terminal back to the shell
+pub fn leave_tui<W: Write>(writer: &mut W) -> io::Result<()> {
+    crossterm::terminal::disable_raw_mode()?;
+    crossterm::execute!(
+        writer,
+        crossterm::terminal::LeaveAlternateScreen,
+        crossterm::event::DisableMouseCapture
+    )
+}
+
+/// Save the terminal's current title on the xterm title stack
+pub fn push_terminal_title<W: Write>(writer: &mut W) -> io::Result<()> {
+    write!(writer, "\x1b[22;0t")?;
+    writer.flush()
+}
+
+/// Restore the title saved by `push_terminal_title`
+pub fn pop_terminal_title<W: Write>(writer: &mut W) -> io::Result<()> {
+    write!(writer, "\x1b[23;0t")?;
+    writer.flush()
+}
+
+/// Spinner frame for something that has been going on for `elapsed`
+fn spinner_frame(elapsed: std::time::Duration) -> char {
+    let frame = elapsed.as_millis() / SPINNER_FRAME_MS;
+    SPINNER_FRAMES[(frame % SPINNER_FRAMES.len() as u128) as usize]
+}
 
 /// Render the tab bar showing all open tabs
 fn render_tabs(f: &mut Frame, app: &App, area: Rect) {
     let titles: Vec<Line> = app
         .tabs
         .iter()
-        .map(|t| Line::from(format!(" {} ", t.page_title)))
+        .map(|t| match t.loading_since {
+            Some(since) if app.config.animations => {
+                Line::from(format!(" {} {} ", spinner_frame(since.elapsed()), t.page_title))
+            }
+            _ => Line::from(format!(" {} ", t.page_title)),
+        })
         .collect();
 
     let tabs = Tabs::new(titles)
@@ -38,6 +96,8 @@ fn render_url_bar(f: &mut Frame, app: &App, area: Rect) {
         InputMode::Editing => Style::default().fg(Color::Yellow),
         InputMode::Visual => Style::default().fg(Color::Blue),
         InputMode::Search => Style::default().fg(Color::Magenta),
+        InputMode::FormInput => Style::default().fg(Color::Green),
+        InputMode::LinkFilter => Style::default().fg(Color::Cyan),
     };
 
     let mode_text = if app.i2p_mode {
@@ -62,11 +122,12 @@ fn render_url_bar(f: &mut Frame, app: &App, area: Rect) {
             let current_index = active_tab
                 .search_state
                 .as_ref()
+                .filter(|s| !s.matches.is_empty())
                 .map(|s| s.current_match_index + 1)
                 .unwrap_or(0);
 
             (
-                query,
+                query.to_string(),
                 format!(
                     "SEARCH - {} [{}/{}] {}",
                     mode_text.trim(),
@@ -76,19 +137,76 @@ fn render_url_bar(f: &mut Frame, app: &App, area: Rect) {
                 ),
             )
         }
+        InputMode::LinkFilter => {
+            let query = active_tab
+                .link_filter
+                .as_ref()
+                .map(|f| f.query.as_str())
+                .unwrap_or("");
+            let match_count = active_tab.link_regions.len();
+            let current_index = if match_count > 0 {
+                active_tab.selected_link_index + 1
+            } else {
+                0
+            };
+            (
+                query.to_string(),
+                format!(
+                    "LINKS - {} [{}/{}] {}",
+                    mode_text.trim(),
+                    current_index,
+                    match_count,
+                    mode_text
+                ),
+            )
+        }
+        // Keep the raw text while editing so the cursor lines up with what is typed
+        InputMode::Editing => (active_tab.url_input.clone(), format!("URL - {}", mode_text)),
         _ => (
-            active_tab.url_input.as_str(),
+            crate::network::display_url(&active_tab.url_input),
             format!("URL - {}", mode_text),
         ),
     };
 
+    let mut title_spans = vec![Span::raw(title)];
+    let is_spoofable = url::Url::parse(&active_tab.url_input)
+        .ok()
+        .and_then(|u| u.host_str().map(crate::network::is_mixed_script_domain))
+        .unwrap_or(false);
+    if is_spoofable {
+        title_spans.push(Span::styled(
+            " ⚠ mixed-script domain ",
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ));
+    }
+    if crate::network::is_insecure_url(&active_tab.url_input) {
+        title_spans.push(Span::styled(
+            " ⚠ insecure ",
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        ));
+    }
+
     let input = Paragraph::new(display_text)
         .style(input_style)
-        .block(Block::default().borders(Borders::ALL).title(title));
+        .block(Block::default().borders(Borders::ALL).title(Line::from(title_spans)));
     f.render_widget(input, area);
+
+    // Show the terminal cursor at the edit position while typing a URL
+    if active_tab.input_mode == InputMode::Editing {
+        let before_cursor: String = active_tab
+            .url_input
+            .chars()
+            .take(active_tab.url_cursor)
+            .collect();
+        let cursor_x = area.x + 1 + before_cursor.width() as u16;
+        if cursor_x < area.right().saturating_sub(1) {
+            f.set_cursor_position((cursor_x, area.y + 1));
+        }
+    }
 }
 
-/// Apply visual mode selection highlighting to content lines
+/// Reverse the selected cells of the lines in view. Selections run either way and end before
+/// `end_char`; every line between the first and last is selected to its end.
 fn apply_visual_highlights(
     lines: &mut [Line],
     selection: &crate::models::Selection,
@@ -120,29 +238,53 @@ fn apply_visual_highlights(
         if current_line_idx < s_line || current_line_idx > e_line {
             continue;
         }
+        let from = if current_line_idx == s_line { s_char } else { 0 };
+        let to = if current_line_idx == e_line { e_char } else { usize::MAX };
+        restyle_columns(line, from, to, |style| style.add_modifier(Modifier::REVERSED));
+    }
+}
 
-        let mut current_x = 0;
-        for span in line.spans.iter_mut() {
-            let span_width = span.width();
-            let span_end = current_x + span_width;
-
-            // Determine if this specific span falls within the selection boundaries
-            let is_selected = if current_line_idx == s_line && current_line_idx == e_line {
-                current_x < e_char && span_end > s_char
-            } else if current_line_idx == s_line {
-                span_end > s_char
-            } else if current_line_idx == e_line {
-                current_x < e_char
+/// Restyle the cells of `line` in display columns `from..to`, splitting spans at the edges so
+/// the rest keep their style. A wide character touching the range is restyled whole, and
+/// combining marks go with the character they follow.
+fn restyle_columns(line: &mut Line, from: usize, to: usize, restyle: impl Fn(Style) -> Style) {
+    if from >= to {
+        return;
+    }
+    let mut spans = Vec::with_capacity(line.spans.len());
+    let mut x = 0;
+    for span in line.spans.drain(..) {
+        let span_end = x + span.width();
+        // Spans wholly inside or outside the range are kept as they are
+        if span_end <= from || x >= to || (x >= from && span_end <= to) {
+            let inside = x >= from && span_end <= to && span_end > x;
+            let style = if inside { restyle(span.style) } else { span.style };
+            spans.push(Span::styled(span.content, style));
+            x = span_end;
+            continue;
+        }
+        // Otherwise cut it into runs of cells that are all in or all out
+        let mut run_start = 0;
+        let mut run_inside = None;
+        for (index, ch) in span.content.char_indices() {
+            let width = ch.width().unwrap_or(0);
+            let inside = if width == 0 {
+                run_inside.unwrap_or(x >= from && x < to)
             } else {
-                true
+                x < to && x + width > from
             };
-
-            if is_selected {
-                span.style = span.style.bg(Color::Blue).fg(Color::White);
+            if run_inside.is_some_and(|previous| previous != inside) {
+                let style = if inside { span.style } else { restyle(span.style) };
+                spans.push(Span::styled(slice_span(&span, run_start, index), style));
+                run_start = index;
             }
-            current_x = span_end;
+            run_inside = Some(inside);
+            x += width;
         }
+        let style = if run_inside == Some(true) { restyle(span.style) } else { span.style };
+        spans.push(Span::styled(slice_span(&span, run_start, span.content.len()), style));
     }
+    line.spans = spans;
 }
 
 /// Apply highlighting to search results
@@ -242,39 +384,94 @@ fn apply_cursor_highlight(
     start_index: usize,
     end_index: usize,
 ) {
-    if cursor_line >= start_index && cursor_line < end_index {
-        let relative_line_idx = cursor_line - start_index;
-        if let Some(line) = lines.get_mut(relative_line_idx) {
-            let mut current_x = 0;
-            for span in line.spans.iter_mut() {
-                let span_width = span.width();
-                let span_end = current_x + span_width;
+    if cursor_line < start_index || cursor_line >= end_index {
+        return;
+    }
+    let Some(line) = lines.get_mut(cursor_line - start_index) else {
+        return;
+    };
 
-                // Check if the cursor_char falls within this span
-                if cursor_char >= current_x && cursor_char < span_end {
-                    // Apply REVERSED style to the span containing the cursor
-                    span.style = span.style.add_modifier(Modifier::REVERSED);
-                    break; // Stop looking once the cursor position is styled
-                }
-                current_x = span_end;
+    // Only the cell under the cursor changes, so multi-character spans (source view, tables)
+    // keep their styling. Reversing toggles, so the cursor still shows inside a selection.
+    let current_x = line.width();
+    if cursor_char < current_x {
+        restyle_columns(line, cursor_char, cursor_char + 1, |style| {
+            if style.add_modifier.contains(Modifier::REVERSED) {
+                style.remove_modifier(Modifier::REVERSED)
+            } else {
+                style.add_modifier(Modifier::REVERSED)
             }
-        }
+        });
+        return;
+    }
+
+    // Past the end of the text (or on an empty line) the cursor sits on a blank cell
+    if cursor_char > current_x {
+        line.spans.push(Span::raw(" ".repeat(cursor_char - current_x)));
+    }
+    line.spans.push(Span::styled(" ", Style::default().add_modifier(Modifier::REVERSED)));
+}
+
+/// Byte range of a span's text, still borrowing from the page when the span does
+fn slice_span<'a>(span: &Span<'a>, from: usize, to: usize) -> Cow<'a, str> {
+    match &span.content {
+        Cow::Borrowed(text) => Cow::Borrowed(&text[from..to]),
+        Cow::Owned(text) => Cow::Owned(text[from..to].to_string()),
+    }
+}
+
+/// Borrow the visible slice of the page as lines whose spans point into the tab's content.
+/// Only the span vectors are rebuilt each frame; the text itself is never copied.
+pub fn viewport_lines<'a>(
+    content: &'a [Line<'static>],
+    start_index: usize,
+    end_index: usize,
+) -> Vec<Line<'a>> {
+    let end_index = end_index.min(content.len());
+    if start_index >= end_index {
+        return Vec::new();
     }
+
+    content[start_index..end_index]
+        .iter()
+        .map(|line| Line {
+            spans: line
+                .spans
+                .iter()
+                .map(|span| Span::styled(span.content.as_ref(), span.style))
+                .collect(),
+            style: line.style,
+            alignment: line.alignment,
+        })
+        .collect()
 }
 
-/// Render the main browser content area with all highlighting applied
+/// Render the main browser content area, split in two panes when a second tab is shown
 fn render_browser_content(f: &mut Frame, app: &App, area: Rect) {
-    let active_tab = &app.tabs[app.active_tab_index];
+    let (focused, other) = app.pane_areas(area);
+    if let (Some(split), Some(other_area)) = (app.split_pane(), other) {
+        render_pane(f, &app.tabs[split.tab_index], other_area, false);
+    }
+    render_pane(f, &app.tabs[app.active_tab_index], focused, true);
+
+    render_download_overlay(f, app, area);
+    render_download_prompt(f, app);
+    render_download_manager(f, app);
+    render_page_info(f, app);
+    render_auth_prompt(f, app);
+}
+
+/// Render one tab's page with all highlighting applied. Only the focused pane shows the
+/// cursor; the other one is drawn with a dimmed border.
+fn render_pane(f: &mut Frame, active_tab: &BrowserTab, area: Rect, focused: bool) {
     let content_area_height = area.height as usize;
-    let start_index = active_tab.scroll;
     let total_lines = active_tab.rendered_content.len();
+    // A scroll left over from a longer page must not slice past the end
+    let start_index = active_tab.scroll.min(total_lines);
     let end_index = (start_index + content_area_height).min(total_lines);
 
-    let mut viewport_content = if start_index < total_lines {
-        active_tab.rendered_content[start_index..end_index].to_vec()
-    } else {
-        Vec::new()
-    };
+    let mut viewport_content =
+        viewport_lines(&active_tab.rendered_content, start_index, end_index);
 
     // Apply visual mode highlighting
     if let (InputMode::Visual, Some(sel)) = (active_tab.input_mode, &active_tab.selection) {
@@ -290,6 +487,25 @@ fn render_browser_content(f: &mut Frame, app: &App, area: Rect) {
         end_index,
     );
 
+    // Mark the form field being typed into
+    if active_tab.input_mode == InputMode::FormInput
+        && let Some(input) = active_tab
+            .focused_input
+            .and_then(|i| active_tab.input_regions.get(i))
+        && input.line_index >= start_index
+        && input.line_index < end_index
+        && let Some(line) = viewport_content.get_mut(input.line_index - start_index)
+    {
+        let mut current_x = 0;
+        for span in line.spans.iter_mut() {
+            let span_end = current_x + span.width();
+            if current_x < input.x_end && span_end > input.x_start {
+                span.style = span.style.add_modifier(Modifier::REVERSED);
+            }
+            current_x = span_end;
+        }
+    }
+
     // Apply search result highlighting
     apply_search_highlights(
         &mut viewport_content,
@@ -299,7 +515,8 @@ fn render_browser_content(f: &mut Frame, app: &App, area: Rect) {
     );
 
     // Apply cursor highlighting for Normal and Visual modes
-    if active_tab.input_mode == InputMode::Normal || active_tab.input_mode == InputMode::Visual {
+    let shows_cursor = matches!(active_tab.input_mode, InputMode::Normal | InputMode::Visual);
+    if focused && shows_cursor {
         apply_cursor_highlight(
             &mut viewport_content,
             active_tab.cursor_line,
@@ -310,19 +527,43 @@ fn render_browser_content(f: &mut Frame, app: &App, area: Rect) {
     }
 
     let status_text = format!("Status: {}", active_tab.status_message);
-    let content = Paragraph::new(viewport_content).scroll((0, 0)).block(
+    // Horizontal offset only; vertical scrolling is done by slicing the viewport
+    let h_scroll = active_tab.h_scroll.min(u16::MAX as usize) as u16;
+    let border_style = if focused {
+        Style::default()
+    } else {
+        Style::default().fg(Color::DarkGray)
+    };
+    let content = Paragraph::new(viewport_content).scroll((0, h_scroll)).block(
         Block::default()
             .borders(Borders::ALL)
+            .border_style(border_style)
             .title(format!("Browser - [{}]", status_text)),
     );
 
     f.render_widget(Clear, area);
     f.render_widget(content, area);
-    render_download_overlay(f, app, area);
-    render_download_prompt(f, app);
+}
+
+/// Shown instead of the normal layout when the terminal can't fit the UI chrome
+fn render_too_small(f: &mut Frame) {
+    let area = f.area();
+    let message = Paragraph::new(vec![
+        Line::from("Terminal too small"),
+        Line::from(format!("Need {} rows", MIN_TERMINAL_HEIGHT)),
+    ])
+    .style(Style::default().fg(Color::Yellow))
+    .alignment(ratatui::layout::Alignment::Center);
+    f.render_widget(Clear, area);
+    f.render_widget(message, area);
 }
 
 pub fn ui(f: &mut Frame, app: &App) {
+    if f.area().height < MIN_TERMINAL_HEIGHT {
+        render_too_small(f);
+        return;
+    }
+
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints(
@@ -345,7 +586,7 @@ fn render_download_overlay(f: &mut Frame, app: &App, area: Rect) {
     if let Some(state) = &app.tabs[app.active_tab_index].download_state {
         let popup_area = Rect {
             x: area.width / 4,
-            y: area.height / 2 - 2,
+            y: (area.height / 2).saturating_sub(2),
             width: area.width / 2,
             height: 5,
         };
@@ -371,6 +612,16 @@ fn render_download_overlay(f: &mut Frame, app: &App, area: Rect) {
                     f.render_widget(gauge, popup_area);
                 }
                 None => {
+                    // Without a total there's no progress to show, only that it's still going
+                    let label = if app.config.animations {
+                        format!(
+                            "{} {} bytes downloaded",
+                            spinner_frame(state.started.elapsed()),
+                            state.bytes_downloaded
+                        )
+                    } else {
+                        format!("{} bytes downloaded", state.bytes_downloaded)
+                    };
                     let gauge = Gauge::default()
                         .block(
                             Block::default()
@@ -379,7 +630,7 @@ fn render_download_overlay(f: &mut Frame, app: &App, area: Rect) {
                         )
                         .gauge_style(Style::default().fg(Color::Cyan))
                         .percent(100)
-                        .label(format!("{} bytes downloaded", state.bytes_downloaded));
+                        .label(label);
                     f.render_widget(gauge, popup_area);
                 }
             },
@@ -399,7 +650,22 @@ fn render_download_overlay(f: &mut Frame, app: &App, area: Rect) {
                 f.render_widget(gauge, popup_area);
             }
 
-            // 3. COMPLETED STATE: Green success bar
+            // 3. CANCELLED STATE: Grey bar, the partial file may have been removed
+            crate::models::DownloadStatus::Cancelled => {
+                let gauge = Gauge::default()
+                    .block(
+                        Block::default()
+                            .borders(Borders::ALL)
+                            .title(" Download Cancelled ")
+                            .title_bottom(" Press ESC to clear "),
+                    )
+                    .gauge_style(Style::default().fg(Color::DarkGray))
+                    .percent(0)
+                    .label(format!("Cancelled: {}", state.filename));
+                f.render_widget(gauge, popup_area);
+            }
+
+            // 4. COMPLETED STATE: Green success bar
             crate::models::DownloadStatus::Completed => {
                 let gauge = Gauge::default()
                     .block(
@@ -421,8 +687,13 @@ fn render_download_prompt(f: &mut Frame, app: &App) {
     if let Some(prompt) = &app.tabs[app.active_tab_index].download_prompt {
         let area = f.area();
 
+        let title = if prompt.conflict.is_some() {
+            " File Exists "
+        } else {
+            " Confirm Download "
+        };
         let block = Block::default()
-            .title(" Confirm Download ")
+            .title(title)
             .borders(Borders::ALL)
             .border_style(Style::default().fg(Color::Yellow));
 
@@ -442,9 +713,17 @@ fn render_download_prompt(f: &mut Frame, app: &App) {
         ];
 
         if prompt.file_exists {
+            let warning = if prompt.conflict.is_some() {
+                "A file with this name already exists."
+            } else {
+                match app.config.download_conflict {
+                    DownloadConflictPolicy::Prompt => "File exists, you'll be asked before replacing it.",
+                    DownloadConflictPolicy::Rename => "File exists, a numbered copy will be saved.",
+                }
+            };
             text.push(Line::from(vec![
                 Span::styled("WARNING: ", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
-                Span::from("File exists and will be overwritten!"),
+                Span::from(warning),
             ]));
         } else {
             text.push(Line::from("Save to Downloads folder?"));
@@ -454,7 +733,11 @@ fn render_download_prompt(f: &mut Frame, app: &App) {
         while (text.len() as u16) < DOWNLOAD_PROMPT_BUTTON_ROW_OFFSET - 1 {
             text.push(Line::from(""));
         }
-        text.push(Line::from(" [Y] Yes   /   [N] No "));
+        if prompt.conflict.is_some() {
+            text.push(Line::from(" [O] Overwrite  [R] Rename  [C] Cancel "));
+        } else {
+            text.push(Line::from(" [Y] Yes   /   [N] No "));
+        }
 
         let paragraph = Paragraph::new(text)
             .block(block)
@@ -463,3 +746,189 @@ fn render_download_prompt(f: &mut Frame, app: &App) {
         f.render_widget(paragraph, popup_area);
     }
 }
+
+/// Format a byte count with a binary unit suffix (e.g. "1.5 MiB")
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+fn download_manager_line(download: &Download, selected: bool) -> Line<'static> {
+    let (marker, color, detail) = match &download.status {
+        DownloadStatus::Active => {
+            let progress = match download.total_size {
+                Some(total) if total > 0 => format!(
+                    "{}% of {}",
+                    download.bytes_downloaded * 100 / total,
+                    format_bytes(total)
+                ),
+                _ => format_bytes(download.bytes_downloaded),
+            };
+            let speed = format_bytes(download.bytes_per_second());
+            ("↓", Color::Yellow, format!("{}  {}/s", progress, speed))
+        }
+        DownloadStatus::Completed => (
+            "✓",
+            Color::Green,
+            format!("Saved: {}", download.saved_path.as_deref().unwrap_or("")),
+        ),
+        DownloadStatus::Failed(msg) => ("✗", Color::Red, format!("Error: {}", msg)),
+        DownloadStatus::Cancelled => ("✗", Color::DarkGray, String::from("Cancelled")),
+    };
+
+    let mut style = Style::default();
+    if selected {
+        style = style.add_modifier(Modifier::REVERSED);
+    }
+    Line::from(vec![
+        Span::styled(format!(" {} ", marker), style.fg(color)),
+        Span::styled(format!("{}  ", download.filename), style.add_modifier(Modifier::BOLD)),
+        Span::styled(detail, style),
+    ])
+}
+
+fn render_download_manager(f: &mut Frame, app: &App) {
+    if !app.show_downloads {
+        return;
+    }
+    let area = f.area();
+    let popup_area = Rect {
+        x: area.width / 10,
+        y: area.height / 6,
+        width: area.width - area.width / 5,
+        height: area.height - area.height / 3,
+    };
+
+    let lines: Vec<Line> = if app.downloads.is_empty() {
+        vec![Line::from(" No downloads yet")]
+    } else {
+        app.downloads
+            .values()
+            .enumerate()
+            .map(|(i, d)| download_manager_line(d, i == app.selected_download))
+            .collect()
+    };
+
+    let block = Block::default()
+        .title(" Downloads ")
+        .title_bottom(" j/k Move  Enter Open  x Cancel/Remove  c Clear finished  Esc Close ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    // Keep the selection in view when the list is taller than the popup
+    let visible = popup_area.height.saturating_sub(2) as usize;
+    let offset = (app.selected_download + 1).saturating_sub(visible);
+
+    f.render_widget(Clear, popup_area);
+    f.render_widget(
+        Paragraph::new(lines).block(block).scroll((offset as u16, 0)),
+        popup_area,
+    );
+}
+
+fn render_page_info(f: &mut Frame, app: &App) {
+    let tab = &app.tabs[app.active_tab_index];
+    if !tab.show_page_info {
+        return;
+    }
+    let area = f.area();
+    let popup_area = Rect {
+        x: area.width / 8,
+        y: area.height / 4,
+        width: area.width - area.width / 4,
+        height: 9.min(area.height),
+    };
+
+    let field = |label: &str, value: String| {
+        Line::from(vec![
+            Span::styled(format!(" {:<16}", label), Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(value),
+        ])
+    };
+    let unknown = || String::from("-");
+    let lines = match &tab.page_info {
+        Some(info) => vec![
+            field("Title", tab.page_title.clone()),
+            field("URL", info.final_url.clone()),
+            field("Status", info.status.to_string()),
+            field("Content-Type", info.content_type.clone().unwrap_or_else(unknown)),
+            field(
+                "Content-Length",
+                info.content_length.map(format_bytes).unwrap_or_else(unknown),
+            ),
+            field("Server", info.server.clone().unwrap_or_else(unknown)),
+        ],
+        None => vec![Line::from(" No response information for this page")],
+    };
+
+    let block = Block::default()
+        .title(" Page Info ")
+        .title_bottom(" i / Esc Close ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+    f.render_widget(Clear, popup_area);
+    f.render_widget(Paragraph::new(lines).block(block), popup_area);
+}
+
+fn render_auth_prompt(f: &mut Frame, app: &App) {
+    let tab = &app.tabs[app.active_tab_index];
+    let Some(prompt) = &tab.auth_prompt else {
+        return;
+    };
+    let area = f.area();
+    let popup_area = Rect {
+        x: area.width / 8,
+        y: area.height / 4,
+        width: area.width - area.width / 4,
+        height: 7.min(area.height),
+    };
+
+    let field = |label: &str, value: String, active: bool| {
+        let marker = if active { ">" } else { " " };
+        let value_style = if active {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default()
+        };
+        Line::from(vec![
+            Span::styled(
+                format!("{} {:<10}", marker, label),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(value, value_style),
+        ])
+    };
+    let realm = prompt
+        .realm
+        .as_ref()
+        .map(|realm| format!(" \"{}\"", realm))
+        .unwrap_or_default();
+    let lines = vec![
+        Line::from(format!(" {}{} wants a username and password", prompt.host, realm)),
+        Line::from(""),
+        field("Username", prompt.username.clone(), !prompt.editing_password),
+        // Only the length of the password is ever drawn
+        field(
+            "Password",
+            "*".repeat(prompt.password.chars().count()),
+            prompt.editing_password,
+        ),
+    ];
+
+    let block = Block::default()
+        .title(" Login Required ")
+        .title_bottom(" Enter Submit · Tab Switch · Esc Cancel ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+    f.render_widget(Clear, popup_area);
+    f.render_widget(Paragraph::new(lines).block(block), popup_area);
+}